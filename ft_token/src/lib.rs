@@ -1,10 +1,12 @@
-mod owner;
+mod upgrade;
 
 use near_contract_standards::fungible_token::events::{FtBurn, FtMint};
 use near_contract_standards::fungible_token::metadata::{
     FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
 };
 use near_contract_standards::fungible_token::FungibleToken;
+use near_contract_tools::owner::Owner;
+use near_contract_tools::Owner;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::LazyOption;
 use near_sdk::json_types::U128;
@@ -14,11 +16,10 @@ use near_sdk::{
 };
 
 #[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault, Owner)]
 pub struct Contract {
     token: FungibleToken,
     metadata: LazyOption<FungibleTokenMetadata>,
-    owner_id: AccountId,
 }
 
 #[derive(BorshSerialize, BorshStorageKey)]
@@ -46,15 +47,19 @@ impl Contract {
         };
         metadata.assert_valid();
 
-        Self {
+        let mut this = Self {
             token: FungibleToken::new(StorageKey::FungibleToken),
             metadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
-            owner_id: owner_id.clone(),
-        }
+        };
+        Owner::init(&mut this, &owner_id);
+        Self::internal_write_state_version();
+        // owner holds remaining proxy and vesting allocations
+        this.token.internal_register_account(&owner_id);
+        this
     }
 
     pub fn ft_mint(&mut self, receiver_id: AccountId, amount: U128) {
-        require!(env::predecessor_account_id() == self.owner_id, "UnAuthorized");
+        Self::require_owner();
 
         self.token.internal_deposit(&receiver_id, amount.0);
         FtMint {
@@ -65,7 +70,7 @@ impl Contract {
     }
 
     pub fn ft_burn(&mut self, from_id: AccountId, amount: U128) {
-        require!(env::predecessor_account_id() == self.owner_id, "UnAuthorized");
+        Self::require_owner();
 
         self.token.internal_withdraw(&from_id, amount.0);
         FtBurn {
@@ -0,0 +1,64 @@
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::{Gas, Promise};
+use crate::*;
+
+const GAS_FOR_MIGRATE: Gas = Gas(20_000_000_000_000);
+
+const STATE_VERSION_KEY: &[u8] = b"~v";
+
+/// Layout version of token state stored under `STATE_VERSION_KEY`
+#[derive(BorshDeserialize, BorshSerialize, PartialEq, Clone, Debug)]
+pub enum StateVersion {
+    /// Unversioned layout with owner kept in contract state
+    V0,
+    V1,
+}
+
+pub(crate) const CURRENT_STATE_VERSION: StateVersion = StateVersion::V1;
+
+/// Token state layout of `StateVersion::V0`
+#[derive(BorshDeserialize)]
+pub struct ContractV0 {
+    token: FungibleToken,
+    metadata: LazyOption<FungibleTokenMetadata>,
+    owner_id: AccountId,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Deploy new code and migrate state, caller should be owner
+    pub fn upgrade(&mut self, code: Base64VecU8) -> Promise {
+        Self::require_owner();
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code.into())
+            .function_call("migrate".to_string(), vec![], 0, GAS_FOR_MIGRATE)
+    }
+
+    /// Migrate state of previous layout, called by `upgrade` after deploying new code.
+    /// Owner of `V0` moves to `Owner` component.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let version = env::storage_read(STATE_VERSION_KEY)
+            .map(|bytes| StateVersion::try_from_slice(&bytes).unwrap())
+            .unwrap_or(StateVersion::V0);
+
+        let contract = match version {
+            StateVersion::V0 => {
+                let old: ContractV0 = env::state_read().unwrap_or_else(|| env::panic_str("No state to migrate"));
+                let mut contract = Self { token: old.token, metadata: old.metadata };
+                Owner::init(&mut contract, &old.owner_id);
+                contract
+            }
+            StateVersion::V1 => env::state_read().unwrap_or_else(|| env::panic_str("No state to migrate")),
+        };
+
+        Self::internal_write_state_version();
+        contract
+    }
+
+    pub(crate) fn internal_write_state_version() {
+        env::storage_write(STATE_VERSION_KEY, &CURRENT_STATE_VERSION.try_to_vec().unwrap());
+    }
+}
@@ -1,22 +1,27 @@
-mod owner;
+mod payout;
+mod upgrade;
 
 use std::collections::HashMap;
 use near_contract_standards::non_fungible_token::events::{NftMint, NftTransfer};
 use near_contract_standards::non_fungible_token::metadata::{NFTContractMetadata, TokenMetadata, NFT_METADATA_SPEC, NonFungibleTokenMetadataProvider};
 use near_contract_standards::non_fungible_token::{Token, TokenId, refund_deposit_to_account};
 use near_contract_standards::non_fungible_token::NonFungibleToken;
+use near_contract_tools::owner::Owner;
+use near_contract_tools::Owner;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{LazyOption, UnorderedSet};
 use near_sdk::{env, near_bindgen, AccountId, BorshStorageKey, PanicOnDefault, Promise, PromiseOrValue, CryptoHash, assert_one_yocto, require};
 use near_sdk::json_types::U128;
 
 #[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault, Owner)]
 pub struct Contract {
     tokens: NonFungibleToken,
     metadata: LazyOption<NFTContractMetadata>,
     current_index: u128,
-    max_supply: u128
+    max_supply: u128,
+    /// Royalty of secondary sales by receiver in basis points
+    royalties: HashMap<AccountId, u32>,
 }
 
 #[derive(BorshSerialize, BorshStorageKey)]
@@ -36,10 +41,12 @@ pub enum NonFungibleTokenStorageKey {
 
 #[near_bindgen]
 impl Contract {
-    /// Initializes the contract owned by `owner_id` with nft metadata
+    /// Initializes the contract owned by `owner_id` with nft metadata and royalties of secondary sales
     #[init]
-    pub fn new(owner_id: AccountId, name: String, symbol: String, base_uri: String, max_supply: U128) -> Self {
+    pub fn new(owner_id: AccountId, name: String, symbol: String, base_uri: String, max_supply: U128, royalties: Option<HashMap<AccountId, u32>>) -> Self {
         assert!(!env::state_exists(), "Already initialized");
+        let royalties = royalties.unwrap_or_default();
+        payout::assert_valid_royalties(&royalties);
         let metadata = NFTContractMetadata {
             spec: NFT_METADATA_SPEC.to_string(),
             name,
@@ -50,18 +57,22 @@ impl Contract {
             reference_hash: None,
         };
 
-        Self {
+        let mut this = Self {
             tokens: NonFungibleToken::new(
                 StorageKey::NonFungibleToken,
-                owner_id,
+                owner_id.clone(),
                 Some(StorageKey::TokenMetadata),
                 Some(StorageKey::Enumeration),
                 Some(StorageKey::Approval),
             ),
             metadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
             current_index: 0u128,
-            max_supply: max_supply.0
-        }
+            max_supply: max_supply.0,
+            royalties,
+        };
+        Owner::init(&mut this, &owner_id);
+        Self::internal_write_state_version();
+        this
     }
 
     /// Mint nft tokens with amount belonging to `receiver_id`.
@@ -74,7 +85,7 @@ impl Contract {
     ) -> Vec<Token> {
         assert!(amount.0 > 0, "Invalid amount");
         assert!(self.current_index.checked_add(amount.0).unwrap() < self.max_supply, "OverMaxSupply");
-        assert_eq!(env::predecessor_account_id(), self.tokens.owner_id, "Unauthorized");
+        Self::require_owner();
 
         let mut tokens: Vec<Token> = vec![];
         let mut i = 0;
@@ -194,6 +205,7 @@ impl NonFungibleTokenMetadataProvider for Contract {
 #[allow(dead_code, unused)]
 #[cfg(test)]
 mod tests {
+    use near_contract_tools::owner::OwnerExternal;
     use near_sdk::{test_utils::*, testing_env, AccountId, ONE_NEAR};
     use super::*;
 
@@ -223,7 +235,8 @@ mod tests {
             String::from("Test FT"),
             String::from("TFT"),
             String::from("https://ipfs.io/ipfs/QmXa5nrfaqrvvcYFeEvs8E9W7AAeCZeUAuN6jophN9y8Ds/"),
-            U128::from(100)
+            U128::from(100),
+            None
         );
 
         // mint
@@ -245,4 +258,148 @@ mod tests {
         assert_eq!(contract.nft_supply_for_owner(accounts(1)), 1u128.into());
         assert_eq!(contract.nft_total_supply(), 1u128.into());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_ownership_transfer() {
+        let owner_id = accounts(0);
+        let alice_id = accounts(1);
+
+        // deploy
+        testing_env!(get_context(owner_id.clone()).build());
+        let mut contract = Contract::new(
+            owner_id.clone(),
+            String::from("Test FT"),
+            String::from("TFT"),
+            String::from("https://ipfs.io/ipfs/QmXa5nrfaqrvvcYFeEvs8E9W7AAeCZeUAuN6jophN9y8Ds/"),
+            U128::from(100),
+            None
+        );
+
+        // propose
+        testing_env!(get_context(owner_id.clone()).attached_deposit(1).build());
+        contract.own_propose_owner(Some(alice_id.clone()));
+        assert_eq!(contract.own_get_owner(), Some(owner_id.clone()));
+        assert_eq!(contract.own_get_proposed_owner(), Some(alice_id.clone()));
+
+        // accept
+        testing_env!(get_context(alice_id.clone()).attached_deposit(1).build());
+        contract.own_accept_owner();
+        assert_eq!(contract.own_get_owner(), Some(alice_id.clone()));
+        assert_eq!(contract.own_get_proposed_owner(), None);
+
+        // new owner mints
+        testing_env!(
+            get_context(alice_id.clone())
+                .attached_deposit(553 * env::storage_byte_cost())
+                .build()
+        );
+        contract.nft_mint(alice_id.clone(), 1u128.into());
+        assert_eq!(contract.nft_supply_for_owner(alice_id.clone()), 1u128.into());
+    }
+
+    #[test]
+    fn test_migrate() {
+        let owner_id = accounts(0);
+
+        // deploy
+        testing_env!(get_context(owner_id.clone()).build());
+        let mut contract = Contract::new(
+            owner_id.clone(),
+            String::from("Test FT"),
+            String::from("TFT"),
+            String::from("https://ipfs.io/ipfs/QmXa5nrfaqrvvcYFeEvs8E9W7AAeCZeUAuN6jophN9y8Ds/"),
+            U128::from(100),
+            None
+        );
+
+        // mint
+        testing_env!(
+            get_context(owner_id.clone())
+                .attached_deposit(553 * env::storage_byte_cost())
+                .build()
+        );
+        contract.nft_mint(accounts(0), 1u128.into());
+
+        // unversioned layout without royalties, owner kept in nft state
+        let state = contract.try_to_vec().unwrap();
+        env::storage_write(b"STATE", &state[..state.len() - 4]);
+        env::storage_remove(b"~v");
+        Contract::slot_is_initialized().remove();
+        Contract::slot_owner().remove();
+
+        testing_env!(get_context(contract_account()).build());
+        let contract = Contract::migrate();
+        assert_eq!(contract.own_get_owner(), Some(owner_id.clone()));
+        assert_eq!(contract.own_get_proposed_owner(), None);
+        assert_eq!(contract.nft_supply_for_owner(accounts(0)), 1u128.into());
+        assert!(contract.get_royalties().is_empty());
+
+        // current layout
+        env::state_write(&contract);
+        let contract = Contract::migrate();
+        assert_eq!(contract.nft_total_supply(), 1u128.into());
+    }
+
+    #[test]
+    fn test_payout() {
+        let owner_id = accounts(0);
+        let alice_id = accounts(1);
+
+        // deploy with 5% royalty to owner and 10% to another account
+        testing_env!(get_context(owner_id.clone()).build());
+        let mut contract = Contract::new(
+            owner_id.clone(),
+            String::from("Test FT"),
+            String::from("TFT"),
+            String::from("https://ipfs.io/ipfs/QmXa5nrfaqrvvcYFeEvs8E9W7AAeCZeUAuN6jophN9y8Ds/"),
+            U128::from(100),
+            Some(HashMap::from([(owner_id.clone(), 500), (accounts(2), 1000)]))
+        );
+
+        // mint
+        testing_env!(
+            get_context(owner_id.clone())
+                .attached_deposit(553 * env::storage_byte_cost())
+                .build()
+        );
+        contract.nft_mint(alice_id.clone(), 1u128.into());
+
+        let payout = contract.nft_payout("0".to_string(), U128(10_000), Some(3)).payout;
+        assert_eq!(payout.len(), 3);
+        assert_eq!(payout[&owner_id], U128(500));
+        assert_eq!(payout[&accounts(2)], U128(1000));
+        assert_eq!(payout[&alice_id], U128(8500));
+
+        // transfer with payout
+        testing_env!(
+            get_context(alice_id.clone())
+                .attached_deposit(1)
+                .build()
+        );
+        let payout = contract.nft_transfer_payout(owner_id.clone(), "0".to_string(), None, None, U128(10_000), None).payout;
+        assert_eq!(payout[&alice_id], U128(8500));
+        assert_eq!(contract.nft_supply_for_owner(owner_id.clone()), 1u128.into());
+
+        // royalty of owner stays with owner
+        let payout = contract.nft_payout("0".to_string(), U128(10_000), Some(2)).payout;
+        assert_eq!(payout.len(), 2);
+        assert_eq!(payout[&owner_id], U128(9000));
+        assert_eq!(payout[&accounts(2)], U128(1000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalty exceeds max royalty")]
+    fn test_max_royalty() {
+        let owner_id = accounts(0);
+
+        testing_env!(get_context(owner_id.clone()).build());
+        Contract::new(
+            owner_id.clone(),
+            String::from("Test FT"),
+            String::from("TFT"),
+            String::from("https://ipfs.io/ipfs/QmXa5nrfaqrvvcYFeEvs8E9W7AAeCZeUAuN6jophN9y8Ds/"),
+            U128::from(100),
+            Some(HashMap::from([(owner_id.clone(), 1500), (accounts(2), 1000)]))
+        );
+    }
+}
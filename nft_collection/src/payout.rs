@@ -0,0 +1,72 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenCore;
+use crate::*;
+
+/// Total royalty of a sale in basis points, 20%
+pub const MAX_ROYALTY: u32 = 2_000;
+/// Royalty receivers, owner of token is paid as well
+pub const MAX_ROYALTY_ACCOUNTS: usize = 10;
+const ROYALTY_DIVISOR: u128 = 10_000;
+
+/// NEP-199 payout of a sale by receiver
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Payout {
+    pub payout: HashMap<AccountId, U128>,
+}
+
+/// Panic unless royalties are payable within limits
+pub fn assert_valid_royalties(royalties: &HashMap<AccountId, u32>) {
+    assert!(royalties.len() <= MAX_ROYALTY_ACCOUNTS, "Too many royalty accounts");
+    let total = royalties.values().fold(0u32, |total, royalty| total.checked_add(*royalty).unwrap());
+    assert!(total <= MAX_ROYALTY, "Royalty exceeds max royalty");
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_royalties(&self) -> HashMap<AccountId, u32> {
+        self.royalties.clone()
+    }
+
+    /// Payout of selling token for `balance`, owner receives what is left after royalties
+    pub fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: Option<u32>) -> Payout {
+        let owner_id = self.tokens.owner_by_id.get(&token_id).unwrap_or_else(|| env::panic_str("Token not found"));
+        self.internal_payout(&owner_id, balance.0, max_len_payout)
+    }
+
+    /// Transfer token and return payout of the sale, payout is computed for owner before transfer
+    #[payable]
+    pub fn nft_transfer_payout(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        balance: U128,
+        max_len_payout: Option<u32>,
+    ) -> Payout {
+        assert_one_yocto();
+        let owner_id = self.tokens.owner_by_id.get(&token_id).unwrap_or_else(|| env::panic_str("Token not found"));
+        let payout = self.internal_payout(&owner_id, balance.0, max_len_payout);
+
+        self.tokens.nft_transfer(receiver_id, token_id, approval_id, memo);
+        payout
+    }
+
+    pub(crate) fn internal_payout(&self, owner_id: &AccountId, balance: u128, max_len_payout: Option<u32>) -> Payout {
+        let receivers = self.royalties.keys().filter(|account_id| *account_id != owner_id).count() + 1;
+        assert!(max_len_payout.map_or(true, |max_len| receivers <= max_len as usize), "Payout exceeds max_len_payout");
+
+        let mut payout = HashMap::new();
+        let mut royalty_amount = 0u128;
+        // royalty of owner is left in owner payout
+        for (account_id, royalty) in self.royalties.iter().filter(|(account_id, _)| *account_id != owner_id) {
+            let amount = balance.checked_mul(*royalty as u128).unwrap() / ROYALTY_DIVISOR;
+            royalty_amount += amount;
+            payout.insert(account_id.clone(), U128(amount));
+        }
+        payout.insert(owner_id.clone(), U128(balance.checked_sub(royalty_amount).unwrap()));
+
+        Payout { payout }
+    }
+}
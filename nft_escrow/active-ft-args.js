@@ -13,5 +13,6 @@ const pre_mint_amount = "10";
 const fund_threshold = (ONE_NEAR * 15n).toString();     // 15 USDT
 const buffer_period = 0;
 const conversion_period = 1800 * 1_000_000_000; // 30 min
+const token_prefix = "tcn1";     // project token account is `tcn1.<escrow-contract-id>`
 
-console.log(JSON.stringify({name, symbol, blank_media_uri, max_supply, finder_id, pre_mint_amount, fund_threshold, buffer_period, conversion_period}));
+console.log(JSON.stringify({name, symbol, blank_media_uri, max_supply, finder_id, pre_mint_amount, fund_threshold, buffer_period, conversion_period, token_prefix}));
@@ -1,2 +1,2 @@
 near call "$(<./neardev/dev-account)" active_nft_project "$(node ./active-nft-args.js)" \
-  --accountId "theia_owner.testnet" --gas 300000000000000
+  --accountId "theia_owner.testnet" --gas 300000000000000 --deposit 6.00125
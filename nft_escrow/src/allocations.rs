@@ -0,0 +1,123 @@
+use crate::*;
+
+const MAX_ALLOCATIONS: usize = 20;
+
+#[near_bindgen]
+impl Contract {
+    /// Register named allocation before activation, caller should be admin.
+    /// Allocations are counted against proxy token max supply.
+    pub fn add_allocation(&mut self, name: String, beneficiary_id: AccountId, amount: U128, schedule: VestingSchedule) -> u32 {
+        self.assert_role(Role::Admin);
+        assert_eq!(self.start_timestamp, 0, "{}", ERR15_ALREADY_ACTIVATED);
+        assert!(self.allocations.len() < MAX_ALLOCATIONS, "{}", ERR73_INVALID_ALLOCATION);
+        assert!(!name.is_empty() && amount.0 > 0, "{}", ERR73_INVALID_ALLOCATION);
+        assert!(schedule.duration > 0 && schedule.cliff <= schedule.duration, "{}", ERR70_INVALID_VESTING);
+
+        self.allocations.push(Allocation {
+            name,
+            beneficiary_id,
+            amount,
+            schedule,
+            claimed_amount: U128(0),
+        });
+
+        let index = self.allocations.len() as u32 - 1;
+        log!("Allocation {} {} {}", index, self.allocations[index as usize].beneficiary_id, amount.0);
+        index
+    }
+
+    /// Remove allocation before activation, caller should be admin
+    pub fn remove_allocation(&mut self, index: u32) {
+        self.assert_role(Role::Admin);
+        assert_eq!(self.start_timestamp, 0, "{}", ERR15_ALREADY_ACTIVATED);
+        assert!((index as usize) < self.allocations.len(), "{}", ERR74_NO_ALLOCATION);
+
+        let allocation = self.allocations.remove(index as usize);
+
+        log!("Remove allocation {} {}", index, allocation.name);
+    }
+
+    /// Mint vested project tokens of allocation, caller should be beneficiary.
+    /// After closing mints unclaimed allocations to escrow, vested tokens are transferred from escrow.
+    pub fn claim_allocation(&mut self, index: u32) -> Promise {
+        self.assert_not_halted();
+        assert!(self.tp_timestamp > 0, "{}", ERR71_VESTING_NOT_STARTED);
+        let tp_timestamp = self.tp_timestamp;
+        let allocation = self.allocations.get_mut(index as usize).unwrap_or_else(|| env::panic_str(ERR74_NO_ALLOCATION));
+        assert_eq!(env::predecessor_account_id(), allocation.beneficiary_id, "{}", ERR75_NOT_BENEFICIARY);
+
+        let vested_amount = allocation.schedule.vested_amount(allocation.amount.0, tp_timestamp, env::block_timestamp());
+        let amount = vested_amount.checked_sub(allocation.claimed_amount.0).unwrap();
+        assert!(amount > 0, "{}", ERR72_NOTHING_TO_CLAIM);
+
+        let beneficiary_id = allocation.beneficiary_id.clone();
+        let claimed_allocation = self.internal_claimed_allocation();
+        self.allocations[index as usize].claimed_amount = U128(vested_amount);
+
+        let claim_promise = match self.allocation_token_offset {
+            Some(token_offset) => self.internal_allocation_transfer(beneficiary_id, token_offset + claimed_allocation, amount),
+            None => self.internal_project_token_mint(beneficiary_id, U128(amount)),
+        };
+        claim_promise
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .on_claim_allocation(index, U128(amount))
+            )
+    }
+
+    #[private]
+    pub fn on_claim_allocation(&mut self, index: u32, amount: U128) -> bool {
+        let allocation = &mut self.allocations[index as usize];
+        if !is_promise_success() {
+            allocation.claimed_amount = U128(allocation.claimed_amount.0.checked_sub(amount.0).unwrap());
+            return false;
+        }
+
+        log!("Claim allocation {} {} {}", index, allocation.beneficiary_id, amount.0);
+        true
+    }
+
+    pub fn get_allocations(&self) -> Vec<Allocation> { self.allocations.clone() }
+
+    pub fn get_allocation_vesting_info(&self, index: u32) -> VestingInfo {
+        let allocation = self.allocations.get(index as usize).unwrap_or_else(|| env::panic_str(ERR74_NO_ALLOCATION));
+        let start_timestamp = if self.tp_timestamp > 0 { Some(self.tp_timestamp) } else { None };
+        let vested_amount = start_timestamp
+            .map_or(0, |start| allocation.schedule.vested_amount(allocation.amount.0, start, env::block_timestamp()));
+
+        VestingInfo {
+            schedule: Some(allocation.schedule.clone()),
+            start_timestamp,
+            total_amount: allocation.amount,
+            vested_amount: U128(vested_amount),
+            claimed_amount: allocation.claimed_amount,
+            claimable_amount: U128(vested_amount.saturating_sub(allocation.claimed_amount.0)),
+        }
+    }
+
+    pub(crate) fn internal_total_allocation(&self) -> Balance {
+        self.allocations.iter().map(|allocation| allocation.amount.0).sum()
+    }
+
+    pub(crate) fn internal_claimed_allocation(&self) -> Balance {
+        self.allocations.iter().map(|allocation| allocation.claimed_amount.0).sum()
+    }
+
+    /// Transfer allocation held by escrow, project token ids start from `from_index`
+    pub(crate) fn internal_allocation_transfer(&self, to: AccountId, from_index: Balance, amount: Balance) -> Promise {
+        match self.project_token_type {
+            ProjectTokenType::NonFungible => {
+                let token_ids: Vec<TokenId> = (from_index..from_index + amount).map(|id| id.to_string()).collect();
+                ext_nft_collection::ext(self.project_token_id.clone().unwrap())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .with_attached_deposit(ONE_YOCTO)
+                    .nft_batch_transfer(to, token_ids, Some("".to_string()))
+            }
+            ProjectTokenType::Fungible => ext_fungible_token::ext(self.project_token_id.clone().unwrap())
+                .with_static_gas(Gas(5 * TGAS))
+                .with_attached_deposit(ONE_YOCTO)
+                .ft_transfer(to, U128::from(amount), None),
+        }
+    }
+}
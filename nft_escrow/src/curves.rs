@@ -67,6 +67,26 @@ impl Contract {
 
     pub fn get_curve_args(&self) -> CurveArgs { self.curve_args.clone() }
 
+    /// Set auction curve before activation, caller should be curve manager
+    pub fn set_curve(&mut self, curve_type: CurveType, curve_args: CurveArgs) {
+        self.assert_role(Role::CurveManager);
+        assert_eq!(self.start_timestamp, 0, "{}", ERR15_ALREADY_ACTIVATED);
+        let is_valid = match curve_type {
+            CurveType::Horizontal => curve_args.arg_a.is_some(),
+            CurveType::Linear => curve_args.arg_a.is_some() && curve_args.arg_b.is_some(),
+            CurveType::Sigmoidal => curve_args.arg_a.is_some()
+                && curve_args.arg_b.is_some()
+                && curve_args.arg_c.is_some()
+                && curve_args.arg_d.is_some(),
+        };
+        assert!(is_valid, "{}", ERR015_INVALID_CURVE_ARGS);
+
+        self.curve_type = curve_type;
+        self.curve_args = curve_args;
+
+        log!("Curve {:?} {:?}", self.curve_type, self.curve_args);
+    }
+
     pub fn get_token_price(&self, token_id: U128) -> u128 {
         if token_id.0 < self.pre_mint_amount {
             return 0u128;
@@ -84,6 +104,12 @@ impl Contract {
     }
 
     pub fn calculate_sell_proxy_token(&self, token_ids: Vec<TokenId>) -> u128 {
+        let reserve_value = self.internal_sell_reserve_value(&token_ids);
+        reserve_value.checked_sub(self.internal_sell_fee(reserve_value)).unwrap()
+    }
+
+    /// Reserve backing proxy tokens, price without treasury fee which was taken on buy
+    pub(crate) fn internal_sell_reserve_value(&self, token_ids: &[TokenId]) -> u128 {
         let mut total_price = 0u128;
         token_ids.iter().enumerate().for_each(|(_, token_id) | {
             total_price = total_price.checked_add(self.get_token_price(U128::from(token_id.parse::<u128>().unwrap()))).unwrap();
@@ -94,4 +120,12 @@ impl Contract {
             .unwrap()
             .checked_div(FEE_DIVISOR as u128).unwrap()
     }
+
+    /// Sell fee withheld from reserve value
+    pub(crate) fn internal_sell_fee(&self, reserve_value: u128) -> u128 {
+        reserve_value
+            .checked_mul(self.sell_fee as u128)
+            .unwrap()
+            .checked_div(FEE_DIVISOR as u128).unwrap()
+    }
 }
\ No newline at end of file
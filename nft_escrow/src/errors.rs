@@ -12,6 +12,31 @@ pub const ERR09_INVALID_ACTION: &str = "E09: Invalid action";
 pub const ERR010_INVALID_AMOUNT: &str = "E10: Invalid amount";
 pub const ERR011_NOT_AVAILABLE_TO_CLOSE: &str = "E11: Invalid amount";
 pub const ERR012_ALREADY_CLOSED: &str = "E12: Project was already closed";
+pub const ERR013_INVALID_HARD_CAP: &str = "E13: Invalid hard cap";
+pub const ERR014_INVALID_SALE_END: &str = "E14: Invalid sale end timestamp";
+pub const ERR015_INVALID_CURVE_ARGS: &str = "E15: Invalid curve args";
+pub const ERR016_INVALID_PROJECT_TOKEN_ID: &str = "E16: Invalid project token account id";
+pub const ERR017_NOT_ALLOW_EXTERNAL: &str = "E17: The action is not allowed for external project token";
+pub const ERR018_INSUFFICIENT_INVENTORY: &str = "E18: Insufficient project token inventory";
+pub const ERR019_ACTIVATION_IN_PROGRESS: &str = "E19: Activation is in progress";
+pub const ERR020_INSUFFICIENT_DEPOSIT: &str = "E20: Insufficient deposit for project token account";
+pub const ERR021_OPERATION_IN_PROGRESS: &str = "E21: Another operation of the account is in progress";
+pub const ERR022_CONVERSION_IN_PROGRESS: &str = "E22: Conversion is in progress";
+pub const ERR023_BALANCE_QUERY_FAILED: &str = "E23: Failed to query stable coin balance";
+pub const ERR024_NOT_ALLOW_RECOVER: &str = "E24: The action is allowed by only owner or treasury";
+pub const ERR025_RESERVE_TOKEN: &str = "E25: Stable coin reserve and project token inventory can not be recovered";
+pub const ERR026_NOT_REGISTERED: &str = "E26: Account is not registered for storage";
+pub const ERR027_INSUFFICIENT_STORAGE: &str = "E27: Insufficient storage deposit";
+pub const ERR028_STORAGE_IN_USE: &str = "E28: Storage deposit is used by proxy tokens";
+pub const ERR029_NO_PENDING_PAYOUT: &str = "E29: No pending payout to withdraw";
+pub const ERR030_NOT_ALLOW_TREASURY: &str = "E30: The action is allowed by only treasury";
+pub const ERR031_INSUFFICIENT_TREASURY_FEE: &str = "E31: Insufficient accrued treasury fee";
+pub const ERR032_INVALID_SELL_FEE: &str = "E32: Sell fee exceeds max sell fee";
+pub const ERR033_INVALID_REFERRER: &str = "E33: Invalid referrer";
+pub const ERR034_INVALID_REFERRAL_FEE: &str = "E34: Referral fee exceeds treasury fee";
+pub const ERR035_NO_REFERRAL_REWARD: &str = "E35: No referral reward to claim";
+pub const ERR036_INVALID_ROYALTY: &str = "E36: Royalty exceeds max royalty";
+pub const ERR037_HOLDERS_NOT_MIGRATED: &str = "E37: Proxy token holders are not migrated";
 
 // Validate errors
 pub const ERR10_NOT_ACTIVATED: &str = "E10: Escrow is not activated";
@@ -20,9 +45,51 @@ pub const ERR12_NOT_OVER_FUNDING_TARGET: &str = "E12: Escrow is not reached to f
 pub const ERR13_IN_BUFFER_PERIOD: &str = "E13: Escrow is in buffer period";
 pub const ERR14_NOT_OVER_CONVERSION_PERIOD: &str = "E14: Escrow is not over conversion period";
 pub const ERR15_ALREADY_ACTIVATED: &str = "E15: Escrow was already activated";
+pub const ERR16_SALE_ENDED: &str = "E16: Escrow sale was ended";
+pub const ERR17_OVER_HARD_CAP: &str = "E17: Escrow is over funding hard cap";
+pub const ERR18_HALTED: &str = "E18: Escrow was halted by holders";
 
 // Owner errors
 pub const ERR20_NOT_ALLOW: &str = "E20: The action is allowed by only owner";
+pub const ERR21_NO_OWNER: &str = "E21: Escrow has no owner";
+pub const ERR22_RENOUNCE_NOT_ALLOWED: &str = "E22: Escrow ownership can not be renounced";
+pub const ERR23_NOT_ALLOW_ROLE: &str = "E23: The action is allowed by only owner or granted role";
 
 // Pause errors
 pub const ERR30_PAUSED: &str = "E20: Escrow was paused";
+
+// Fund release errors
+pub const ERR40_INVALID_RELEASE_MODE: &str = "E40: Invalid fund release mode";
+pub const ERR41_RELEASE_BY_PROPOSAL: &str = "E41: Fund should be released by proposal";
+pub const ERR42_NO_PROPOSAL: &str = "E42: Fund release proposal does not exist";
+pub const ERR43_INVALID_PROPOSAL_STATUS: &str = "E43: Invalid fund release proposal status";
+pub const ERR44_PROPOSAL_EXPIRED: &str = "E44: Fund release proposal was expired";
+pub const ERR45_NOT_APPROVER: &str = "E45: The action is allowed by only signer or DAO";
+pub const ERR46_ALREADY_APPROVED: &str = "E46: Fund release proposal was already approved by signer";
+
+// Milestone errors
+pub const ERR50_INVALID_MILESTONES: &str = "E50: Invalid milestones";
+pub const ERR51_NO_MILESTONE: &str = "E51: Milestone does not exist";
+pub const ERR52_INVALID_MILESTONE_STATUS: &str = "E52: Invalid milestone status";
+pub const ERR53_NOT_VERIFIER: &str = "E53: The action is allowed by only milestone verifier";
+
+// Halt errors
+pub const ERR60_INVALID_HALT_CONFIG: &str = "E60: Invalid halt config";
+pub const ERR61_NO_VOTE_WEIGHT: &str = "E61: Account has no voting weight";
+pub const ERR62_HALT_PROPOSAL_IN_VOTING: &str = "E62: Another halt proposal is in voting";
+pub const ERR63_NO_HALT_PROPOSAL: &str = "E63: Halt proposal does not exist";
+pub const ERR64_NOT_IN_VOTING: &str = "E64: Halt proposal is not in voting";
+pub const ERR65_ALREADY_VOTED: &str = "E65: Account already voted";
+pub const ERR66_NOT_HALTED: &str = "E66: Escrow is not halted";
+
+// Vesting errors
+pub const ERR70_INVALID_VESTING: &str = "E70: Invalid vesting schedule";
+pub const ERR71_VESTING_NOT_STARTED: &str = "E71: Vesting is not started";
+pub const ERR72_NOTHING_TO_CLAIM: &str = "E72: Nothing vested to claim";
+pub const ERR73_INVALID_ALLOCATION: &str = "E73: Invalid allocation";
+pub const ERR74_NO_ALLOCATION: &str = "E74: Allocation does not exist";
+pub const ERR75_NOT_BENEFICIARY: &str = "E75: The action is allowed by only beneficiary";
+
+// Upgrade errors
+pub const ERR80_NO_STATE: &str = "E80: No state to migrate";
+pub const ERR81_HOLDERS_MIGRATED: &str = "E81: Proxy token holders are already migrated";
@@ -0,0 +1,62 @@
+use near_contract_tools::Event;
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::AccountId;
+
+/// Escrow events following NEP-297
+#[derive(Event, Serialize)]
+#[event(standard = "theia-escrow", version = "1.0.0", rename_all = "snake_case")]
+#[serde(crate = "near_sdk::serde", untagged)]
+pub enum EscrowEvent {
+    FundReleaseProposed {
+        proposal_id: u64,
+        proposer_id: AccountId,
+        to: AccountId,
+        amount: U128,
+        expires_at: u64,
+    },
+    FundReleaseApproved {
+        proposal_id: u64,
+        approver_id: AccountId,
+        approvals: u32,
+    },
+    FundReleaseExecuted {
+        proposal_id: u64,
+        amount: U128,
+    },
+    FundReleaseFailed {
+        proposal_id: u64,
+        amount: U128,
+    },
+    FundReleaseCancelled {
+        proposal_id: u64,
+    },
+    MilestoneSubmitted {
+        index: u32,
+        evidence: String,
+    },
+    MilestoneApproved {
+        index: u32,
+        verifier_id: AccountId,
+    },
+    HaltProposed {
+        proposal_id: u64,
+        proposer_id: AccountId,
+        reason: String,
+        ends_at: u64,
+    },
+    HaltVoted {
+        proposal_id: u64,
+        voter_id: AccountId,
+        support: bool,
+        weight: U128,
+    },
+    HaltFinalized {
+        proposal_id: u64,
+        passed: bool,
+    },
+    HaltRefunded {
+        account_id: AccountId,
+        amount: U128,
+    },
+}
@@ -0,0 +1,85 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Active project on already deployed project token, caller should be admin.
+    /// Escrow does not mint project token, pre-minted tokens are transferred in as conversion inventory.
+    #[allow(clippy::too_many_arguments)]
+    pub fn active_external_project(&mut self, token_id: AccountId, token_type: ProjectTokenType, max_supply: U128, finder_id: AccountId, fund_threshold: U128, buffer_period: u64, conversion_period: u64) {
+        self.assert_role(Role::Admin);
+        assert_eq!(self.start_timestamp, 0, "{}", ERR15_ALREADY_ACTIVATED);
+        assert!(self.pending_activation.is_none(), "{}", ERR019_ACTIVATION_IN_PROGRESS);
+        assert!(self.closed_step == ClosedStep::None, "{}", ERR012_ALREADY_CLOSED);
+        assert!(max_supply.0 > 0 && self.pre_mint_amount < max_supply.0, "{}", ERR04_INVALID_MAX_SUPPLY);
+        assert!(fund_threshold.0 > 0, "{}", ERR05_INVALID_FUNDING_TARGET);
+        assert!(conversion_period >= 86400, "{}", ERR06_INVALID_CONVERSION_PERIOD);
+        assert!(self.fund_hard_cap.map_or(true, |cap| cap >= fund_threshold.0), "{}", ERR013_INVALID_HARD_CAP);
+        assert!(self.sale_end_timestamp.map_or(true, |end| end > env::block_timestamp()), "{}", ERR014_INVALID_SALE_END);
+        // vesting claims mint or take fixed token ids, both need escrow deployed project token
+        assert!(self.allocations.is_empty() && self.pre_mint_vesting.is_none(), "{}", ERR017_NOT_ALLOW_EXTERNAL);
+
+        self.finder_id = Some(finder_id);
+        self.fund_threshold = fund_threshold.0;
+        self.buffer_period = buffer_period;
+        self.conversion_period = conversion_period;
+        self.project_token_type = token_type;
+        self.pt_max_supply = max_supply.0;
+        self.external_project = true;
+        self.project_token_id = Some(token_id.clone());
+        self.start_timestamp = env::block_timestamp();
+
+        log!("Activated {} {}", token_id.to_string(), self.start_timestamp);
+    }
+
+    pub fn is_external_project(&self) -> bool { self.external_project }
+
+    /// Amount of project token held by escrow for conversion
+    pub fn get_inventory_amount(&self) -> U128 { U128(self.internal_inventory_amount()) }
+
+    pub(crate) fn internal_inventory_amount(&self) -> Balance {
+        match self.project_token_type {
+            ProjectTokenType::NonFungible => self.inventory_token_ids.len() as u128,
+            ProjectTokenType::Fungible => self.inventory_amount,
+        }
+    }
+
+    /// Transfer project token from inventory, returns nft token ids taken from inventory
+    pub(crate) fn internal_inventory_transfer(&mut self, to: AccountId, amount: u128) -> (Promise, Vec<TokenId>) {
+        assert!(self.internal_inventory_amount() >= amount, "{}", ERR018_INSUFFICIENT_INVENTORY);
+
+        match self.project_token_type {
+            ProjectTokenType::NonFungible => {
+                let token_ids: Vec<TokenId> = (0..amount).map(|_| self.inventory_token_ids.pop().unwrap()).collect();
+                let promise = ext_nft_collection::ext(self.project_token_id.clone().unwrap())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .with_attached_deposit(ONE_YOCTO)
+                    .nft_batch_transfer(
+                        to,
+                        token_ids.clone(),
+                        Some("".to_string()),
+                    );
+                (promise, token_ids)
+            }
+            ProjectTokenType::Fungible => {
+                self.inventory_amount -= amount;
+                let promise = ext_fungible_token::ext(self.project_token_id.clone().unwrap())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .with_attached_deposit(ONE_YOCTO)
+                    .ft_transfer(
+                        to,
+                        U128::from(amount),
+                        None,
+                    );
+                (promise, vec![])
+            }
+        }
+    }
+
+    /// Put back project token of failed transfer into inventory
+    pub(crate) fn internal_revert_inventory_transfer(&mut self, amount: u128, token_ids: Vec<TokenId>) {
+        match self.project_token_type {
+            ProjectTokenType::NonFungible => token_ids.iter().for_each(|token_id| self.inventory_token_ids.push(token_id)),
+            ProjectTokenType::Fungible => self.inventory_amount += amount,
+        }
+    }
+}
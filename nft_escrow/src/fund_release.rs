@@ -0,0 +1,158 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Set how raised fund is released, caller should be owner.
+    /// Can not be changed after activation so holders can rely on it.
+    pub fn set_fund_release_mode(&mut self, mode: FundReleaseMode, proposal_period: u64) {
+        Self::require_owner();
+        assert_eq!(self.start_timestamp, 0, "{}", ERR15_ALREADY_ACTIVATED);
+        match &mode {
+            FundReleaseMode::Owner => {}
+            FundReleaseMode::Multisig { signers, threshold } => {
+                let mut unique_signers = signers.clone();
+                unique_signers.sort();
+                unique_signers.dedup();
+                assert!(
+                    unique_signers.len() == signers.len() && *threshold > 0 && *threshold as usize <= signers.len(),
+                    "{}",
+                    ERR40_INVALID_RELEASE_MODE
+                );
+                assert!(proposal_period > 0, "{}", ERR40_INVALID_RELEASE_MODE);
+            }
+            FundReleaseMode::Dao { .. } => {
+                assert!(proposal_period > 0, "{}", ERR40_INVALID_RELEASE_MODE);
+            }
+        }
+
+        self.fund_release_mode = mode;
+        self.fund_release_proposal_period = proposal_period;
+
+        log!("Fund release mode {:?} {}", self.fund_release_mode, proposal_period);
+    }
+
+    /// Propose fund release, caller should be fund claimer
+    pub fn propose_fund_release(&mut self, to: AccountId, amount: U128) -> u64 {
+        self.assert_role(Role::FundClaimer);
+        assert!(self.fund_release_mode != FundReleaseMode::Owner, "{}", ERR40_INVALID_RELEASE_MODE);
+        self.assert_is_after_conversion_period();
+
+        assert!(amount.0 > 0 && self.internal_available_fund() >= amount.0, "{}", ERR010_INVALID_AMOUNT);
+
+        let proposal_id = self.next_fund_release_id;
+        let proposal = FundReleaseProposal {
+            id: proposal_id,
+            proposer_id: env::predecessor_account_id(),
+            to,
+            amount,
+            approvals: vec![],
+            status: ProposalStatus::Pending,
+            expires_at: env::block_timestamp().checked_add(self.fund_release_proposal_period).unwrap(),
+        };
+        self.fund_release_proposals.insert(&proposal_id, &proposal);
+        self.next_fund_release_id += 1;
+
+        EscrowEvent::FundReleaseProposed {
+            proposal_id,
+            proposer_id: proposal.proposer_id,
+            to: proposal.to,
+            amount,
+            expires_at: proposal.expires_at,
+        }.emit();
+
+        proposal_id
+    }
+
+    /// Approve fund release, caller should be signer in multisig mode or DAO in DAO mode.
+    /// Fund is transferred once enough approvals are collected.
+    pub fn approve_fund_release(&mut self, proposal_id: u64) -> PromiseOrValue<bool> {
+        let approver_id = env::predecessor_account_id();
+        let mut proposal = self.internal_unwrap_fund_release(proposal_id);
+        assert_eq!(proposal.status, ProposalStatus::Pending, "{}", ERR43_INVALID_PROPOSAL_STATUS);
+        assert!(env::block_timestamp() < proposal.expires_at, "{}", ERR44_PROPOSAL_EXPIRED);
+
+        let threshold = match &self.fund_release_mode {
+            FundReleaseMode::Owner => env::panic_str(ERR40_INVALID_RELEASE_MODE),
+            FundReleaseMode::Multisig { signers, threshold } => {
+                assert!(signers.contains(&approver_id), "{}", ERR45_NOT_APPROVER);
+                *threshold
+            }
+            FundReleaseMode::Dao { dao_id } => {
+                assert_eq!(&approver_id, dao_id, "{}", ERR45_NOT_APPROVER);
+                1
+            }
+        };
+        assert!(!proposal.approvals.contains(&approver_id), "{}", ERR46_ALREADY_APPROVED);
+
+        proposal.approvals.push(approver_id.clone());
+        EscrowEvent::FundReleaseApproved {
+            proposal_id,
+            approver_id,
+            approvals: proposal.approvals.len() as u32,
+        }.emit();
+
+        if (proposal.approvals.len() as u32) < threshold {
+            self.fund_release_proposals.insert(&proposal_id, &proposal);
+            return PromiseOrValue::Value(false);
+        }
+
+        PromiseOrValue::Promise(self.internal_execute_fund_release(proposal))
+    }
+
+    /// Retry transfer of approved fund release
+    pub fn execute_fund_release(&mut self, proposal_id: u64) -> Promise {
+        let proposal = self.internal_unwrap_fund_release(proposal_id);
+        assert_eq!(proposal.status, ProposalStatus::Approved, "{}", ERR43_INVALID_PROPOSAL_STATUS);
+        assert!(env::block_timestamp() < proposal.expires_at, "{}", ERR44_PROPOSAL_EXPIRED);
+
+        // reservation of the proposal is checked again as its claim
+        self.approved_fund_release_amount = self.approved_fund_release_amount.checked_sub(proposal.amount.0).unwrap();
+        self.internal_execute_fund_release(proposal)
+    }
+
+    /// Cancel fund release, caller should be proposer or owner
+    pub fn cancel_fund_release(&mut self, proposal_id: u64) {
+        let mut proposal = self.internal_unwrap_fund_release(proposal_id);
+        let predecessor_id = env::predecessor_account_id();
+        assert!(predecessor_id == proposal.proposer_id || self.is_owner(&predecessor_id), "{}", ERR20_NOT_ALLOW);
+        assert!(
+            proposal.status == ProposalStatus::Pending || proposal.status == ProposalStatus::Approved,
+            "{}",
+            ERR43_INVALID_PROPOSAL_STATUS
+        );
+
+        if proposal.status == ProposalStatus::Approved {
+            self.approved_fund_release_amount = self.approved_fund_release_amount.checked_sub(proposal.amount.0).unwrap();
+        }
+        proposal.status = ProposalStatus::Cancelled;
+        self.fund_release_proposals.insert(&proposal_id, &proposal);
+
+        EscrowEvent::FundReleaseCancelled { proposal_id }.emit();
+    }
+
+    pub fn get_fund_release_mode(&self) -> FundReleaseMode { self.fund_release_mode.clone() }
+
+    pub fn get_fund_release_proposal(&self, proposal_id: u64) -> Option<FundReleaseProposal> {
+        self.fund_release_proposals.get(&proposal_id)
+    }
+
+    pub fn get_fund_release_proposals(&self, from_index: Option<u64>, limit: Option<u64>) -> Vec<FundReleaseProposal> {
+        let from_index = from_index.unwrap_or(0);
+        let limit = limit.unwrap_or(self.next_fund_release_id);
+        (from_index..std::cmp::min(from_index.saturating_add(limit), self.next_fund_release_id))
+            .filter_map(|proposal_id| self.fund_release_proposals.get(&proposal_id))
+            .collect()
+    }
+
+    pub(crate) fn internal_unwrap_fund_release(&self, proposal_id: u64) -> FundReleaseProposal {
+        self.fund_release_proposals.get(&proposal_id).unwrap_or_else(|| env::panic_str(ERR42_NO_PROPOSAL))
+    }
+
+    pub(crate) fn internal_execute_fund_release(&mut self, mut proposal: FundReleaseProposal) -> Promise {
+        // mark as executed before transfer, `on_claim_fund` reverts it to approved on failure
+        proposal.status = ProposalStatus::Executed;
+        self.fund_release_proposals.insert(&proposal.id, &proposal);
+
+        self.internal_claim_fund(proposal.to, proposal.amount, Some(proposal.id))
+    }
+}
@@ -0,0 +1,255 @@
+use crate::*;
+
+const GAS_FOR_ON_CLAIM_HALT_REFUND: Gas = Gas(30 * TGAS);
+
+#[near_bindgen]
+impl Contract {
+    /// Set holder voting config to halt project before activation, caller should be admin
+    pub fn set_halt_config(&mut self, config: HaltConfig) {
+        self.assert_role(Role::Admin);
+        assert_eq!(self.start_timestamp, 0, "{}", ERR15_ALREADY_ACTIVATED);
+        assert!(
+            config.quorum <= FEE_DIVISOR && config.threshold > 0 && config.threshold <= FEE_DIVISOR && config.voting_period > 0,
+            "{}",
+            ERR60_INVALID_HALT_CONFIG
+        );
+
+        self.halt_config = config;
+
+        log!("Halt config {} {} {}", self.halt_config.quorum, self.halt_config.threshold, self.halt_config.voting_period);
+    }
+
+    /// Propose to halt project and refund remaining reserve, caller should hold proxy tokens
+    pub fn create_halt_proposal(&mut self, reason: String) -> u64 {
+        self.assert_not_halted();
+        self.assert_holders_migrated();
+        assert!(self.start_timestamp > 0, "{}", ERR10_NOT_ACTIVATED);
+        assert!(self.active_halt_proposal_id.is_none(), "{}", ERR62_HALT_PROPOSAL_IN_VOTING);
+
+        let proposer_id = env::predecessor_account_id();
+        assert!(self.internal_vote_weight(&proposer_id, None) > 0, "{}", ERR61_NO_VOTE_WEIGHT);
+
+        let proposal_id = self.next_halt_proposal_id;
+        let ends_at = env::block_timestamp().checked_add(self.halt_config.voting_period).unwrap();
+        self.halt_proposals.insert(&proposal_id, &HaltProposal {
+            id: proposal_id,
+            proposer_id: proposer_id.clone(),
+            reason: reason.clone(),
+            snapshot_height: env::block_height(),
+            total_weight: U128(self.internal_total_vote_weight()),
+            votes_for: U128(0),
+            votes_against: U128(0),
+            ends_at,
+            status: HaltProposalStatus::Voting,
+        });
+        self.next_halt_proposal_id += 1;
+        self.active_halt_proposal_id = Some(proposal_id);
+
+        EscrowEvent::HaltProposed { proposal_id, proposer_id, reason, ends_at }.emit();
+
+        proposal_id
+    }
+
+    /// Vote on halt proposal with proxy tokens held at proposal snapshot
+    pub fn vote_halt(&mut self, proposal_id: u64, support: bool) {
+        let mut proposal = self.internal_unwrap_halt_proposal(proposal_id);
+        assert!(
+            proposal.status == HaltProposalStatus::Voting && env::block_timestamp() < proposal.ends_at,
+            "{}",
+            ERR64_NOT_IN_VOTING
+        );
+
+        self.assert_holders_migrated();
+        let voter_id = env::predecessor_account_id();
+        assert_ne!(self.halt_votes.get(&voter_id), Some(proposal_id), "{}", ERR65_ALREADY_VOTED);
+        // buys in the proposal block do not count, snapshot is the end of previous block
+        let weight = self.internal_vote_weight(&voter_id, Some(proposal.snapshot_height - 1));
+        assert!(weight > 0, "{}", ERR61_NO_VOTE_WEIGHT);
+
+        if support {
+            proposal.votes_for = U128(proposal.votes_for.0.checked_add(weight).unwrap());
+        } else {
+            proposal.votes_against = U128(proposal.votes_against.0.checked_add(weight).unwrap());
+        }
+        self.halt_proposals.insert(&proposal_id, &proposal);
+        self.halt_votes.insert(&voter_id, &proposal_id);
+
+        EscrowEvent::HaltVoted { proposal_id, voter_id, support, weight: U128(weight) }.emit();
+    }
+
+    /// Finalize halt proposal after voting period, anyone can call.
+    /// Passed proposal halts project and opens refund of remaining reserve.
+    pub fn finalize_halt_proposal(&mut self, proposal_id: u64) -> bool {
+        let mut proposal = self.internal_unwrap_halt_proposal(proposal_id);
+        assert!(
+            proposal.status == HaltProposalStatus::Voting && env::block_timestamp() >= proposal.ends_at,
+            "{}",
+            ERR64_NOT_IN_VOTING
+        );
+
+        let votes = proposal.votes_for.0.checked_add(proposal.votes_against.0).unwrap();
+        let passed = votes > 0
+            && votes * FEE_DIVISOR as u128 >= proposal.total_weight.0 * self.halt_config.quorum as u128
+            && proposal.votes_for.0 * FEE_DIVISOR as u128 > votes * self.halt_config.threshold as u128;
+
+        proposal.status = if passed { HaltProposalStatus::Passed } else { HaltProposalStatus::Rejected };
+        self.halt_proposals.insert(&proposal_id, &proposal);
+        self.active_halt_proposal_id = None;
+        if passed && self.halted_timestamp.is_none() {
+            self.halted_timestamp = Some(env::block_timestamp());
+        }
+
+        EscrowEvent::HaltFinalized { proposal_id, passed }.emit();
+
+        passed
+    }
+
+    /// Claim pro-rata share of remaining reserve after project was halted.
+    /// Bought proxy tokens of caller are burned, converted amount is refunded as well.
+    pub fn claim_halt_refund(&mut self) -> Promise {
+        assert!(self.halted_timestamp.is_some(), "{}", ERR66_NOT_HALTED);
+
+        let account_id = env::predecessor_account_id();
+        let weight = self.internal_vote_weight(&account_id, None);
+        assert!(weight > 0, "{}", ERR61_NO_VOTE_WEIGHT);
+
+        let refund = self.internal_halt_refund(weight);
+        // reserve the weight until the transfer is resolved
+        self.internal_lock_account(&account_id);
+        self.internal_checkpoint_vote_weight(&account_id, 0);
+
+        // pre-minted tokens of owner are not weighted, so only bought proxy tokens are burned
+        let proxy_amount = weight.saturating_sub(self.internal_account_history(&account_id).converted_amount);
+        let token_ids: Vec<TokenId> = self
+            .pt_tokens_for_owner(account_id.clone())
+            .into_iter()
+            .filter(|token_id| !self.internal_is_pre_mint_token(&account_id, token_id))
+            .flat_map(|token_id| {
+                let balance = self.internal_unwrap_balance_of(&token_id, &account_id);
+                std::iter::repeat(token_id).take(balance as usize)
+            })
+            .take(proxy_amount as usize)
+            .collect();
+        if !token_ids.is_empty() {
+            self.pt_burn(account_id.clone(), token_ids.clone());
+        }
+
+        ext_fungible_token::ext(self.stable_coin_id.clone())
+            .with_static_gas(Gas(5 * TGAS))
+            .with_attached_deposit(ONE_YOCTO)
+            .ft_transfer(
+                account_id.clone(),
+                U128::from(refund),
+                None,
+            )
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_CLAIM_HALT_REFUND)
+                    .on_claim_halt_refund(account_id, U128(weight), U128(refund), token_ids)
+            )
+    }
+
+    #[private]
+    pub fn on_claim_halt_refund(&mut self, account_id: AccountId, weight: U128, amount: U128, token_ids: Vec<TokenId>) -> bool {
+        self.internal_unlock_account(&account_id);
+        if !is_promise_success() {
+            self.internal_checkpoint_vote_weight(&account_id, weight.0);
+            if !token_ids.is_empty() {
+                self.revert_pt_burn(account_id, token_ids);
+            }
+            return false;
+        }
+
+        let burned_amount = token_ids.len() as u128;
+        self.total_fund_amount = self.total_fund_amount.checked_sub(amount.0).unwrap();
+        self.circulating_supply = self.circulating_supply.checked_sub(burned_amount).unwrap();
+        self.unweighted_converted_amount = self.unweighted_converted_amount.checked_add(weight.0 - burned_amount).unwrap();
+        self.internal_release_storage(&account_id, burned_amount);
+
+        let mut history = self.internal_account_history(&account_id);
+        history.refunded_amount = history.refunded_amount.checked_add(amount.0).unwrap();
+        self.account_histories.insert(&account_id, &history);
+
+        EscrowEvent::HaltRefunded { account_id, amount }.emit();
+
+        true
+    }
+
+    pub fn get_halt_config(&self) -> HaltConfig { self.halt_config.clone() }
+
+    pub fn get_halt_proposal(&self, proposal_id: u64) -> Option<HaltProposal> { self.halt_proposals.get(&proposal_id) }
+
+    pub fn get_active_halt_proposal_id(&self) -> Option<u64> { self.active_halt_proposal_id }
+
+    pub fn get_halted_timestamp(&self) -> Option<u64> { self.halted_timestamp }
+
+    /// Voting weight of account, at the end of `block_height` if given.
+    /// Checkpoints are compacted to the snapshot of proposal in voting, the previous block and the latest one,
+    /// so weight at other past heights is the one of earlier kept checkpoint.
+    pub fn get_vote_weight(&self, account_id: AccountId, block_height: Option<u64>) -> U128 {
+        U128(self.internal_vote_weight(&account_id, block_height))
+    }
+
+    /// Refundable amount of account after project was halted
+    pub fn get_halt_refund(&self, account_id: AccountId) -> U128 {
+        let weight = self.internal_vote_weight(&account_id, None);
+        if self.halted_timestamp.is_none() || weight == 0 {
+            return U128(0);
+        }
+        U128(self.internal_halt_refund(weight))
+    }
+
+    pub(crate) fn internal_unwrap_halt_proposal(&self, proposal_id: u64) -> HaltProposal {
+        self.halt_proposals.get(&proposal_id).unwrap_or_else(|| env::panic_str(ERR63_NO_HALT_PROPOSAL))
+    }
+
+    /// Proxy tokens bought and not yet sold, converted amount included until refunded
+    pub(crate) fn internal_total_vote_weight(&self) -> Balance {
+        self.circulating_supply.checked_sub(self.unweighted_converted_amount).unwrap()
+    }
+
+    pub(crate) fn internal_vote_weight(&self, account_id: &AccountId, block_height: Option<u64>) -> Balance {
+        self.vote_checkpoints
+            .get(account_id)
+            .and_then(|checkpoints| {
+                checkpoints
+                    .into_iter()
+                    .rev()
+                    .find(|checkpoint| block_height.map_or(true, |height| checkpoint.block_height <= height))
+            })
+            .map_or(0, |checkpoint| checkpoint.weight)
+    }
+
+    /// Only weights at snapshot of proposal in voting, before current block and latest are read by votes,
+    /// so at most three checkpoints are kept and their storage is covered by account registration
+    pub(crate) fn internal_checkpoint_vote_weight(&mut self, account_id: &AccountId, weight: Balance) {
+        let block_height = env::block_height();
+        let snapshot_height = self.active_halt_proposal_id
+            .map(|proposal_id| self.internal_unwrap_halt_proposal(proposal_id).snapshot_height - 1);
+        let checkpoints = self.vote_checkpoints.get(account_id).unwrap_or_default();
+
+        let mut compacted: Vec<VoteCheckpoint> = snapshot_height
+            .and_then(|height| checkpoints.iter().rev().find(|checkpoint| checkpoint.block_height <= height).cloned())
+            .into_iter()
+            .collect();
+        // proposal created later in this block snapshots the weight before it
+        if let Some(previous) = checkpoints.into_iter().rev().find(|checkpoint| checkpoint.block_height < block_height) {
+            if compacted.last().map_or(true, |snapshot| snapshot.block_height < previous.block_height) {
+                compacted.push(previous);
+            }
+        }
+        compacted.push(VoteCheckpoint { block_height, weight });
+        self.vote_checkpoints.insert(account_id, &compacted);
+    }
+
+    /// Share of reserve left after fund and finder fee claims and sells in flight
+    pub(crate) fn internal_halt_refund(&self, weight: Balance) -> Balance {
+        let reserve = self.total_fund_amount
+            .checked_sub(self.claimed_fund_amount + self.pending_fund_claim).unwrap()
+            .checked_sub(self.claimed_finder_fee + self.pending_finder_fee_claim).unwrap()
+            .checked_sub(self.pending_sell_refund).unwrap();
+        reserve
+            .checked_mul(weight).unwrap()
+            .checked_div(self.internal_total_vote_weight().checked_sub(self.pending_sell_amount).unwrap()).unwrap()
+    }
+}
@@ -0,0 +1,118 @@
+use near_sdk::PromiseError;
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Check escrow accounting agrees with itself, stable coin balance is not queried
+    pub fn check_invariants(&self) -> InvariantReport {
+        self.internal_invariant_report(None)
+    }
+
+    /// Check escrow accounting with actual stable coin balance of escrow, caller should be owner
+    pub fn reconcile_stable_coin(&mut self) -> Promise {
+        Self::require_owner();
+
+        ext_fungible_token::ext(self.stable_coin_id.clone())
+            .with_static_gas(Gas(5 * TGAS))
+            .ft_balance_of(env::current_account_id())
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(Gas(10 * TGAS))
+                    .on_reconcile_stable_coin()
+            )
+    }
+
+    #[private]
+    pub fn on_reconcile_stable_coin(&self, #[callback_result] balance: Result<U128, PromiseError>) -> InvariantReport {
+        let balance = balance.unwrap_or_else(|_| env::panic_str(ERR023_BALANCE_QUERY_FAILED));
+        let report = self.internal_invariant_report(Some(balance.0));
+
+        if !report.is_valid {
+            log!("Invariants violated {}", report.violations.join(", "));
+        }
+        report
+    }
+
+    /// Stable coin escrow should hold for reserve, failed payouts, accrued treasury fee and referral rewards, in-flight transfers are already excluded
+    pub(crate) fn internal_expected_stable_coin_balance(&self) -> Balance {
+        self.total_fund_amount
+            .saturating_sub(self.claimed_fund_amount + self.pending_fund_claim)
+            .saturating_sub(self.claimed_finder_fee + self.pending_finder_fee_claim)
+            .saturating_sub(self.pending_sell_refund)
+            .checked_add(self.total_pending_payout)
+            .unwrap()
+            .checked_add(self.accrued_treasury_fee)
+            .unwrap()
+            .checked_add(self.unclaimed_referral_reward)
+            .unwrap()
+    }
+
+    pub(crate) fn internal_invariant_report(&self, stable_coin_balance: Option<Balance>) -> InvariantReport {
+        let mut violations: Vec<String> = vec![];
+        let mut check = |valid: bool, violation: &str| {
+            if !valid {
+                violations.push(violation.to_string());
+            }
+        };
+
+        let claimed_amount = self.claimed_fund_amount
+            + self.pending_fund_claim
+            + self.claimed_finder_fee
+            + self.pending_finder_fee_claim
+            + self.pending_sell_refund;
+        check(claimed_amount <= self.total_fund_amount, "claimed fund and finder fee exceed total fund");
+        // halt refunds shrink total fund after claims stopped
+        if self.halted_timestamp.is_none() {
+            check(
+                self.claimed_fund_amount + self.pending_fund_claim <= self.internal_total_claimable_fund(),
+                "claimed fund exceeds claimable fund"
+            );
+            check(
+                self.claimed_finder_fee + self.pending_finder_fee_claim <= self.internal_total_finder_fee(),
+                "claimed finder fee exceeds total finder fee"
+            );
+        }
+
+        check(self.converted_amount <= self.circulating_supply, "converted amount exceeds circulating supply");
+        check(
+            self.pt_all_total_supply + self.internal_total_allocation() <= self.pt_max_supply || self.start_timestamp == 0,
+            "proxy token supply and allocations exceed max supply"
+        );
+
+        let remain_proxys = self.circulating_supply.saturating_sub(self.converted_amount);
+        check(
+            self.pending_sell_amount + self.pending_convert_amount <= remain_proxys,
+            "pending sells and conversions exceed remaining proxy tokens"
+        );
+        // sold and converted proxy tokens are burned before callbacks update circulating supply
+        check(
+            remain_proxys.saturating_sub(self.pending_sell_amount + self.pending_convert_amount) <= self.pt_all_total_supply,
+            "circulating supply exceeds proxy token supply"
+        );
+        if self.external_project {
+            check(
+                remain_proxys.saturating_sub(self.pending_convert_amount) <= self.internal_inventory_amount(),
+                "remaining proxy tokens exceed project token inventory"
+            );
+        }
+
+        let expected_stable_coin_balance = self.internal_expected_stable_coin_balance();
+        if let Some(balance) = stable_coin_balance {
+            // buys waiting for callbacks and stray transfers only add to balance
+            check(balance >= expected_stable_coin_balance, "stable coin balance is less than reserve");
+        }
+
+        InvariantReport {
+            is_valid: violations.is_empty(),
+            violations,
+            total_fund_amount: U128(self.total_fund_amount),
+            claimed_fund_amount: U128(self.claimed_fund_amount),
+            claimed_finder_fee: U128(self.claimed_finder_fee),
+            circulating_supply: U128(self.circulating_supply),
+            converted_amount: U128(self.converted_amount),
+            pt_all_total_supply: U128(self.pt_all_total_supply),
+            expected_stable_coin_balance: U128(expected_stable_coin_balance),
+            stable_coin_balance: stable_coin_balance.map(U128),
+        }
+    }
+}
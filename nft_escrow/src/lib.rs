@@ -8,23 +8,43 @@ mod pause;
 mod token_receiver;
 mod pt_metadata;
 mod proxy_token;
-
+mod roles;
+mod events;
+mod fund_release;
+mod milestones;
+mod halt;
+mod vesting;
+mod allocations;
+mod upgrade;
+mod external;
+mod pending;
+mod invariants;
+mod recovery;
+mod storage;
+mod payouts;
+mod treasury;
+mod referral;
+
+use std::collections::HashMap;
 use near_contract_standards::non_fungible_token::TokenId;
+use near_contract_tools::owner::Owner;
+use near_contract_tools::Rbac;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::env::STORAGE_PRICE_PER_BYTE;
 use near_sdk::{env, near_bindgen, AccountId, Balance, BorshStorageKey, PanicOnDefault, Promise, Gas, log, is_promise_success, PromiseOrValue};
-use near_sdk::collections::{LookupMap, UnorderedMap};
+use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet, Vector};
 use near_sdk::json_types::U128;
 use near_sdk::serde_json::json;
+use near_contract_tools::event::Event;
 use crate::pt_metadata::*;
+use crate::events::*;
 use crate::errors::*;
 use crate::utils::*;
 
 #[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault, Rbac)]
+#[rbac(roles = "Role")]
 pub struct Contract {
-    /// Owner of contract
-    owner_id: AccountId,
     /// Project token name
     name: String,
     /// Project token symbol
@@ -41,8 +61,20 @@ pub struct Contract {
     project_token_type: ProjectTokenType,
     /// Project token id
     project_token_id: Option<AccountId>,
+    /// Activation waiting for project token deployment
+    pending_activation: Option<PendingActivation>,
+    /// Project token was deployed outside of escrow
+    external_project: bool,
+    /// Fungible project token held for conversion of external project
+    inventory_amount: Balance,
+    /// Non-fungible project token ids held for conversion of external project
+    inventory_token_ids: Vector<TokenId>,
     /// Funding target amount
     fund_threshold: Balance,
+    /// Funding hard cap amount
+    fund_hard_cap: Option<Balance>,
+    /// Sale end timestamp
+    sale_end_timestamp: Option<u64>,
     /// Start timestamp
     start_timestamp: u64,
     /// Threshold timestamp
@@ -61,8 +93,89 @@ pub struct Contract {
     claimed_fund_amount: Balance,
     /// Total claimed finder fee amount
     claimed_finder_fee: Balance,
+    /// Fund amount of claims waiting for transfer
+    pending_fund_claim: Balance,
+    /// Finder fee amount of claims waiting for transfer
+    pending_finder_fee_claim: Balance,
+    /// Reserve fund of buys waiting for proxy token mint
+    pending_buy_fund: Balance,
+    /// Stable coin deposit of buys waiting for proxy token mint
+    pending_buy_deposit: Balance,
+    /// Stable coin of payout, withdraw and claim transfers waiting for their callbacks
+    pending_payout_transfer: Balance,
+    /// Refund amount of sells waiting for transfer
+    pending_sell_refund: Balance,
+    /// Proxy token amount of sells waiting for transfer
+    pending_sell_amount: Balance,
+    /// Proxy token amount of conversions waiting for project token transfer
+    pending_convert_amount: Balance,
+    /// Accounts having sell, convert or refund in flight
+    pending_accounts: LookupSet<AccountId>,
+    /// Stable coin of failed claim, fee and change transfers by account id, withdrawn with `withdraw_pending`
+    pending_payouts: UnorderedMap<AccountId, Balance>,
+    /// Total stable coin of failed transfers
+    total_pending_payout: Balance,
+    /// Treasury fee of buys not claimed yet by treasury
+    accrued_treasury_fee: Balance,
+    /// Accrue treasury fee in escrow instead of transferring it on every buy
+    treasury_fee_accrual: bool,
+    /// Fee withheld from sell refunds (base 10_000)
+    sell_fee: u32,
+    /// Where fee withheld from sell refunds goes
+    sell_fee_destination: SellFeeDestination,
+    /// Part of treasury fee credited to referrer of buy (base 10_000)
+    referral_fee: u32,
+    /// Referral rewards by referrer
+    referral_accounts: LookupMap<AccountId, ReferralAccount>,
+    /// Referral rewards not claimed yet
+    unclaimed_referral_reward: Balance,
+    /// Fund release mode
+    fund_release_mode: FundReleaseMode,
+    /// Lifetime of fund release proposal
+    fund_release_proposal_period: u64,
+    /// Next fund release proposal id
+    next_fund_release_id: u64,
+    /// Fund release proposals by id
+    fund_release_proposals: LookupMap<u64, FundReleaseProposal>,
+    /// Fund of approved proposals waiting for `execute_fund_release`
+    approved_fund_release_amount: Balance,
+    /// Milestones unlocking raised fund
+    milestones: Vec<Milestone>,
+    /// Account approving milestones
+    milestone_verifier_id: Option<AccountId>,
+    /// Holder voting config to halt project
+    halt_config: HaltConfig,
+    /// Next halt proposal id
+    next_halt_proposal_id: u64,
+    /// Halt proposal in voting
+    active_halt_proposal_id: Option<u64>,
+    /// Halt proposals by id
+    halt_proposals: LookupMap<u64, HaltProposal>,
+    /// Last halt proposal id voted by account id
+    halt_votes: LookupMap<AccountId, u64>,
+    /// Voting weight checkpoints by account id
+    vote_checkpoints: LookupMap<AccountId, Vec<VoteCheckpoint>>,
+    /// Halted timestamp
+    halted_timestamp: Option<u64>,
+    /// Converted amount without voting weight, refunded after halt or converted before `V0` migration
+    unweighted_converted_amount: Balance,
     /// Pre-mint amount
     pre_mint_amount: Balance,
+    /// Vesting schedule of pre-mint allocation
+    pre_mint_vesting: Option<VestingSchedule>,
+    /// Vesting start timestamp, set when pre-mint is minted to escrow on closing
+    pre_mint_vesting_start: Option<u64>,
+    /// First project token id of pre-mint allocation
+    pre_mint_token_offset: Balance,
+    /// Claimed amount of vested pre-mint allocation
+    pre_mint_claimed_amount: Balance,
+    /// Named allocations vesting to beneficiaries
+    allocations: Vec<Allocation>,
+    /// Token id offset of allocations held by escrow, added to claimed allocation amount.
+    /// Set when closing mints unclaimed allocations to escrow after remaining proxies
+    allocation_token_offset: Option<Balance>,
+    /// First project token id of remaining proxies minted on closing
+    remain_proxy_token_offset: Balance,
     /// Amount of converted proxy token
     converted_amount: Balance,
     /// Circulating supply of proxy token
@@ -85,6 +198,14 @@ pub struct Contract {
     pt_total_supply: LookupMap<TokenId, Balance>,
     /// Proxy token balance by token id and account id
     pt_balances_per_token: UnorderedMap<TokenId, LookupMap<AccountId, Balance>>,
+    /// Proxy token ids by account id
+    pt_tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    /// Proxy tokens held before `V0` migration and not yet indexed by `migrate_holders`
+    unindexed_proxy_amount: Balance,
+    /// Paid, refunded and converted amounts by account id
+    account_histories: LookupMap<AccountId, AccountHistory>,
+    /// Storage deposit paying proxy tokens by account id
+    storage_accounts: LookupMap<AccountId, AccountStorage>,
 }
 
 #[derive(BorshSerialize, BorshStorageKey)]
@@ -92,16 +213,35 @@ enum StorageKey {
     TotalSupply { supply: u128 },
     Balances,
     BalancesInner { token_id: Vec<u8> },
+    TokensPerOwner,
+    TokensPerOwnerInner { account_hash: Vec<u8> },
+    AccountHistories,
+    FundReleaseProposals,
+    HaltProposals,
+    HaltVotes,
+    VoteCheckpoints,
+    InventoryTokenIds,
+    PendingAccounts,
+    StorageAccounts,
+    PendingPayouts,
+    ReferralAccounts,
 }
 
 const MIN_STORAGE_NON_FUNGIBLE_TOKEN: Balance = 600_000 * STORAGE_PRICE_PER_BYTE;
 const MIN_STORAGE_FUNGIBLE_TOKEN: Balance = 600_000 * STORAGE_PRICE_PER_BYTE;
 const DEPOSIT_ONE_NFT_MINT: Balance = 638 * STORAGE_PRICE_PER_BYTE;
-const DEPOSIT_ONE_PT_MINT: Balance = 415 * STORAGE_PRICE_PER_BYTE;
+const DEPOSIT_ONE_PT_MINT: Balance = 640 * STORAGE_PRICE_PER_BYTE;
+const DEPOSIT_PT_OWNER: Balance = 250 * STORAGE_PRICE_PER_BYTE;
+const FT_STORAGE_DEPOSIT: Balance = 125 * STORAGE_PRICE_PER_BYTE;
 const NO_DEPOSIT: Balance = 0u128;
 const ONE_YOCTO: Balance = 1u128;
 const TGAS: u64 = 1_000_000_000_000;
 const GAS_FOR_PT_MINT: Gas = Gas(100 * TGAS);
+const GAS_FOR_ON_BUY: Gas = Gas(30 * TGAS);
+const GAS_FOR_ON_SELL: Gas = Gas(30 * TGAS);
+const MAX_SELL_FEE: u32 = 1_000;  // 10%
+const MAX_ROYALTY: u32 = 2_000;  // 20%, same as nft collection
+const MAX_ROYALTY_ACCOUNTS: usize = 10;
 
 const NFT_COLLECTION_CODE: &[u8] = include_bytes!("../../target/wasm32-unknown-unknown/release/nft_collection.wasm");
 const FUNGIBLE_TOKEN_CODE: &[u8] = include_bytes!("../../target/wasm32-unknown-unknown/release/ft_token.wasm");
@@ -110,14 +250,16 @@ const FUNGIBLE_TOKEN_CODE: &[u8] = include_bytes!("../../target/wasm32-unknown-u
 impl Contract {
     /// Initialize the contract
     #[init]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(owner_id: AccountId, name: String, symbol: String, pt_media_uri: String, stable_coin_id: AccountId, stable_coin_decimals: u8, curve_type: CurveType, curve_args: CurveArgs, treasury_id: AccountId) -> Self {
         assert!(!env::state_exists(), "{}", ERR08_ALREADY_INITIALIZED);
         assert!(name.len() > 2, "{}", ERR00_INVALID_NAME);
         assert!(symbol.len() < 13 && symbol.len() > 2, "{}", ERR01_INVALID_SYMBOL);
         assert!(pt_media_uri.len() > 0, "{}", ERR03_INVALID_PT_MEDIA_URI);
 
-        Self {
-            owner_id,
+        Self::internal_write_state_version();
+
+        let mut this = Self {
             name,
             symbol,
             treasury_id,
@@ -126,7 +268,13 @@ impl Contract {
             finder_fee: 100,    // 1%
             project_token_type: ProjectTokenType::NonFungible,
             project_token_id: None,
+            pending_activation: None,
+            external_project: false,
+            inventory_amount: 0,
+            inventory_token_ids: Vector::new(StorageKey::InventoryTokenIds),
             fund_threshold: 0,
+            fund_hard_cap: None,
+            sale_end_timestamp: None,
             start_timestamp: 0,
             tp_timestamp: 0,
             buffer_period: 0,
@@ -136,7 +284,51 @@ impl Contract {
             total_fund_amount: 0,
             claimed_fund_amount: 0,
             claimed_finder_fee: 0,
+            pending_fund_claim: 0,
+            pending_finder_fee_claim: 0,
+            pending_buy_fund: 0,
+            pending_buy_deposit: 0,
+            pending_payout_transfer: 0,
+            pending_sell_refund: 0,
+            pending_sell_amount: 0,
+            pending_convert_amount: 0,
+            pending_accounts: LookupSet::new(StorageKey::PendingAccounts),
+            pending_payouts: UnorderedMap::new(StorageKey::PendingPayouts),
+            total_pending_payout: 0,
+            accrued_treasury_fee: 0,
+            treasury_fee_accrual: true,
+            sell_fee: 0,
+            sell_fee_destination: SellFeeDestination::Reserve,
+            referral_fee: 0,
+            referral_accounts: LookupMap::new(StorageKey::ReferralAccounts),
+            unclaimed_referral_reward: 0,
+            fund_release_mode: FundReleaseMode::Owner,
+            fund_release_proposal_period: 0,
+            next_fund_release_id: 0,
+            fund_release_proposals: LookupMap::new(StorageKey::FundReleaseProposals),
+            approved_fund_release_amount: 0,
+            milestones: vec![],
+            milestone_verifier_id: None,
+            halt_config: HaltConfig {
+                quorum: 2000,       // 20%
+                threshold: 5000,    // 50%
+                voting_period: 7 * 86400 * 1_000_000_000,
+            },
+            next_halt_proposal_id: 0,
+            active_halt_proposal_id: None,
+            halt_proposals: LookupMap::new(StorageKey::HaltProposals),
+            halt_votes: LookupMap::new(StorageKey::HaltVotes),
+            vote_checkpoints: LookupMap::new(StorageKey::VoteCheckpoints),
+            halted_timestamp: None,
+            unweighted_converted_amount: 0,
             pre_mint_amount: 0,
+            pre_mint_vesting: None,
+            pre_mint_vesting_start: None,
+            pre_mint_token_offset: 0,
+            pre_mint_claimed_amount: 0,
+            allocations: vec![],
+            allocation_token_offset: None,
+            remain_proxy_token_offset: 0,
             converted_amount: 0,
             circulating_supply: 0,
             curve_type,
@@ -146,33 +338,74 @@ impl Contract {
             pt_media_uri,
             pt_total_supply: LookupMap::new(StorageKey::TotalSupply { supply: u128::MAX }),
             pt_balances_per_token: UnorderedMap::new(StorageKey::Balances),
+            pt_tokens_per_owner: LookupMap::new(StorageKey::TokensPerOwner),
+            unindexed_proxy_amount: 0,
+            account_histories: LookupMap::new(StorageKey::AccountHistories),
+            storage_accounts: LookupMap::new(StorageKey::StorageAccounts),
             pt_max_supply: 0,
             pt_all_total_supply: 0
-        }
+        };
+        Owner::init(&mut this, &owner_id);
+        this
     }
 
     /// Pre-mint
     #[payable]
     pub fn pre_mint(&mut self, amount: U128) {
-        self.assert_owner();
+        self.assert_role(Role::Admin);
         assert!(amount.0 > 0, "{}", ERR010_INVALID_AMOUNT);
         assert_eq!(self.start_timestamp, 0, "{}", ERR15_ALREADY_ACTIVATED);
 
         self.pre_mint_amount = self.pre_mint_amount.checked_add(amount.0).unwrap();
 
-        self.pt_mint(self.owner_id.clone(), amount);
+        self.pt_mint(self.internal_owner_id(), amount);
 
         log!("Pre-mint {}", amount.0);
     }
 
-    /// Active NFT project
-    pub fn active_nft_project(&mut self, base_uri: String, max_supply: U128, finder_id: AccountId, fund_threshold: U128, buffer_period: u64, conversion_period: u64) -> Promise {
-        self.assert_owner();
+    /// Set funding hard cap and sale end timestamp
+    pub fn set_sale_limits(&mut self, hard_cap: Option<U128>, sale_end_timestamp: Option<u64>) {
+        self.assert_role(Role::Admin);
+        assert_eq!(self.start_timestamp, 0, "{}", ERR15_ALREADY_ACTIVATED);
+        assert!(hard_cap.map_or(true, |cap| cap.0 > 0), "{}", ERR013_INVALID_HARD_CAP);
+        assert!(sale_end_timestamp.map_or(true, |end| end > env::block_timestamp()), "{}", ERR014_INVALID_SALE_END);
+
+        self.fund_hard_cap = hard_cap.map(|cap| cap.0);
+        self.sale_end_timestamp = sale_end_timestamp;
+
+        log!("Sale limits {:?} {:?}", self.fund_hard_cap, self.sale_end_timestamp);
+    }
+
+    /// Set protocol fee receiver, caller should be treasury operator
+    pub fn set_treasury_id(&mut self, treasury_id: AccountId) {
+        self.assert_role(Role::TreasuryOperator);
+        self.treasury_id = treasury_id;
+
+        log!("Treasury {}", self.treasury_id);
+    }
+
+    /// Active NFT project, attached deposit covers project token account and treasury registration on stable coin.
+    /// Excess deposit is refunded. `royalties` of secondary sales in basis points are set on collection.
+    #[payable]
+    #[allow(clippy::too_many_arguments)]
+    pub fn active_nft_project(&mut self, base_uri: String, max_supply: U128, finder_id: AccountId, fund_threshold: U128, buffer_period: u64, conversion_period: u64, token_prefix: Option<String>, royalties: Option<HashMap<AccountId, u32>>) -> Promise {
+        self.assert_role(Role::Admin);
         assert!(self.closed_step == ClosedStep::None, "{}", ERR012_ALREADY_CLOSED);
         assert!(base_uri.len() > 0, "{}", ERR02_INVALID_COLLECTION_BASE_URI);
-        assert!(max_supply.0 > 0 && self.pre_mint_amount < max_supply.0, "{}", ERR04_INVALID_MAX_SUPPLY);
+        assert!(
+            royalties.as_ref().map_or(true, |royalties| royalties.len() <= MAX_ROYALTY_ACCOUNTS && royalties.values().map(|royalty| *royalty as u64).sum::<u64>() <= MAX_ROYALTY as u64),
+            "{}",
+            ERR036_INVALID_ROYALTY
+        );
+        assert!(max_supply.0 > 0 && self.pre_mint_amount + self.internal_total_allocation() < max_supply.0, "{}", ERR04_INVALID_MAX_SUPPLY);
         assert!(fund_threshold.0 > 0, "{}", ERR05_INVALID_FUNDING_TARGET);
         assert!(conversion_period >= 86400, "{}", ERR06_INVALID_CONVERSION_PERIOD);
+        assert!(self.fund_hard_cap.map_or(true, |cap| cap >= fund_threshold.0), "{}", ERR013_INVALID_HARD_CAP);
+        assert!(self.sale_end_timestamp.map_or(true, |end| end > env::block_timestamp()), "{}", ERR014_INVALID_SALE_END);
+        let project_token_id = self.internal_project_token_account(token_prefix);
+        self.internal_begin_activation(MIN_STORAGE_NON_FUNGIBLE_TOKEN + FT_STORAGE_DEPOSIT);
+        // treasury receives fee of every buy
+        self.internal_ft_storage_deposit(self.stable_coin_id.clone(), self.treasury_id.clone(), FT_STORAGE_DEPOSIT);
 
         self.finder_id = Some(finder_id);
         self.fund_threshold = fund_threshold.0;
@@ -181,10 +414,6 @@ impl Contract {
         self.project_token_type = ProjectTokenType::NonFungible;
         self.pt_max_supply = max_supply.0;
 
-        let mut token_suffix = self.name.clone().to_lowercase();
-        token_suffix.retain(|c| !c.is_whitespace());
-        let project_token_id = AccountId::new_unchecked(format!("{}.{}", token_suffix, env::current_account_id()));
-
         // deploy non-fungible token
         let project_token_promise = Promise::new(project_token_id.clone())
             .create_account()
@@ -197,7 +426,8 @@ impl Contract {
                     "name": self.name.clone(),
                     "symbol": self.symbol.clone(),
                     "base_uri": base_uri,
-                    "max_supply": max_supply
+                    "max_supply": max_supply,
+                    "royalties": royalties
                 }).to_string().as_bytes().to_vec(),
                 NO_DEPOSIT,
                 Gas(5 * TGAS)
@@ -211,13 +441,21 @@ impl Contract {
             )
     }
 
-    /// Active FT project
-    pub fn active_ft_project(&mut self, max_supply: U128, finder_id: AccountId, fund_threshold: U128, buffer_period: u64, conversion_period: u64) -> Promise {
-        self.assert_owner();
+    /// Active FT project, attached deposit covers project token account and treasury registration on stable coin.
+    /// Excess deposit is refunded.
+    #[payable]
+    pub fn active_ft_project(&mut self, max_supply: U128, finder_id: AccountId, fund_threshold: U128, buffer_period: u64, conversion_period: u64, token_prefix: Option<String>) -> Promise {
+        self.assert_role(Role::Admin);
         assert!(self.closed_step == ClosedStep::None, "{}", ERR012_ALREADY_CLOSED);
-        assert!(max_supply.0 > 0 && self.pre_mint_amount < max_supply.0, "{}", ERR04_INVALID_MAX_SUPPLY);
+        assert!(max_supply.0 > 0 && self.pre_mint_amount + self.internal_total_allocation() < max_supply.0, "{}", ERR04_INVALID_MAX_SUPPLY);
         assert!(fund_threshold.0 > 0, "{}", ERR05_INVALID_FUNDING_TARGET);
         assert!(conversion_period >= 86400, "{}", ERR06_INVALID_CONVERSION_PERIOD);
+        assert!(self.fund_hard_cap.map_or(true, |cap| cap >= fund_threshold.0), "{}", ERR013_INVALID_HARD_CAP);
+        assert!(self.sale_end_timestamp.map_or(true, |end| end > env::block_timestamp()), "{}", ERR014_INVALID_SALE_END);
+        let project_token_id = self.internal_project_token_account(token_prefix);
+        self.internal_begin_activation(MIN_STORAGE_FUNGIBLE_TOKEN + FT_STORAGE_DEPOSIT);
+        // treasury receives fee of every buy
+        self.internal_ft_storage_deposit(self.stable_coin_id.clone(), self.treasury_id.clone(), FT_STORAGE_DEPOSIT);
 
         self.finder_id = Some(finder_id);
         self.fund_threshold = fund_threshold.0;
@@ -226,10 +464,6 @@ impl Contract {
         self.project_token_type = ProjectTokenType::Fungible;
         self.pt_max_supply = max_supply.0;
 
-        let mut token_suffix = self.name.clone().to_lowercase();
-        token_suffix.retain(|c| !c.is_whitespace());
-        let project_token_id = AccountId::new_unchecked(format!("{}.{}", token_suffix, env::current_account_id()));
-
         // deploy fungible token
         let project_token_promise = Promise::new(project_token_id.clone())
             .create_account()
@@ -260,12 +494,29 @@ impl Contract {
         &mut self,
         project_token_id: AccountId
     ) -> bool {
+        let pending = self.pending_activation.take().unwrap();
         if is_promise_success() {
             self.project_token_id = Some(project_token_id.clone());
             self.start_timestamp = env::block_timestamp();
 
+            let refund = pending.deposit.checked_sub(pending.storage_cost).unwrap();
+            if refund > 0 {
+                Promise::new(pending.caller_id).transfer(refund);
+            }
+
             log!("Activated {} {}", project_token_id.to_string(), self.start_timestamp);
         } else {
+            // failed deployment returns storage cost to escrow
+            self.finder_id = pending.finder_id;
+            self.fund_threshold = pending.fund_threshold;
+            self.buffer_period = pending.buffer_period;
+            self.conversion_period = pending.conversion_period;
+            self.project_token_type = pending.project_token_type;
+            self.pt_max_supply = pending.pt_max_supply;
+            // treasury registration deposit was spent or refunded already
+            Promise::new(pending.caller_id).transfer(pending.deposit.checked_sub(FT_STORAGE_DEPOSIT).unwrap());
+
+            log!("Activation failed {}", project_token_id.to_string());
             return false;
         }
 
@@ -273,40 +524,60 @@ impl Contract {
     }
 
     /// buy proxy token
-    pub(crate) fn buy(&mut self, from: AccountId, amount: U128, deposit: U128) -> Promise {
+    pub(crate) fn buy(&mut self, from: AccountId, amount: U128, deposit: U128, referrer_id: Option<AccountId>) -> Promise {
         self.assert_not_paused();
+        self.assert_holders_migrated();
+        self.assert_not_halted();
         self.assert_is_ongoing();
         assert!(amount.0 > 0, "Invalid amount");
-        assert!(self.pt_all_total_supply + amount.0 < self.pt_max_supply, "OverMaxSupply");
+        assert!(self.pt_all_total_supply + self.internal_total_allocation() + amount.0 < self.pt_max_supply, "OverMaxSupply");
+        assert!(!self.external_project || self.pt_all_total_supply + amount.0 <= self.internal_inventory_amount(), "{}", ERR018_INSUFFICIENT_INVENTORY);
 
         let cal_coin_amount = self.calculate_buy_proxy_token(amount);
         assert!(deposit.0 >= cal_coin_amount, "{}", ERR07_INSUFFICIENT_FUND);
+        assert!(referrer_id.as_ref() != Some(&from), "{}", ERR033_INVALID_REFERRER);
+        // referrer pays storage of its reward ledger with registration
+        assert!(referrer_id.as_ref().map_or(true, |referrer_id| self.storage_accounts.get(referrer_id).is_some()), "{}", ERR026_NOT_REGISTERED);
+
+        let treasury_fee_amount = cal_coin_amount
+            .checked_mul(self.treasury_fee as u128)
+            .unwrap()
+            .checked_div(FEE_DIVISOR as u128)
+            .unwrap();
+        let reserve_fund_amount = cal_coin_amount.checked_sub(treasury_fee_amount).unwrap();
+        self.assert_is_on_sale(reserve_fund_amount);
+        // count buy against hard cap until `on_buy`
+        self.pending_buy_fund = self.pending_buy_fund.checked_add(reserve_fund_amount).unwrap();
+        self.pending_buy_deposit = self.pending_buy_deposit.checked_add(deposit.0).unwrap();
+        // buyer pays storage of minted proxy tokens
+        self.internal_charge_storage(&from, amount.0);
 
         // Mint proxy token to customer
         let mint_promise = ext_self::ext(env::current_account_id())
             .with_static_gas(GAS_FOR_PT_MINT)
-            .with_attached_deposit(amount.0 * DEPOSIT_ONE_PT_MINT)
+            .with_attached_deposit(amount.0 * DEPOSIT_ONE_PT_MINT + DEPOSIT_PT_OWNER)
             .pt_mint(from.clone(), amount);
 
         mint_promise.then(
                 ext_self::ext(env::current_account_id())
-                    .with_static_gas(Gas(5 * TGAS))
-                    .on_buy(from, amount, deposit, U128(cal_coin_amount))
+                    .with_static_gas(GAS_FOR_ON_BUY)
+                    .on_buy(from, amount, deposit, U128(cal_coin_amount), referrer_id)
             )
     }
 
     #[private]
     #[payable]
-    pub fn on_buy(&mut self, from: AccountId, amount: U128, deposit: U128, reserve: U128) -> bool {
-        if is_promise_success() {
-            let treasury_fee_amount = reserve.0
-                .checked_mul(self.treasury_fee as u128)
-                .unwrap()
-                .checked_div(FEE_DIVISOR as u128)
-                .unwrap();
-
-            let reserve_fund_amount = reserve.0.checked_sub(treasury_fee_amount).unwrap();
+    pub fn on_buy(&mut self, from: AccountId, amount: U128, deposit: U128, reserve: U128, referrer_id: Option<AccountId>) -> bool {
+        let treasury_fee_amount = reserve.0
+            .checked_mul(self.treasury_fee as u128)
+            .unwrap()
+            .checked_div(FEE_DIVISOR as u128)
+            .unwrap();
+        let reserve_fund_amount = reserve.0.checked_sub(treasury_fee_amount).unwrap();
+        self.pending_buy_fund = self.pending_buy_fund.checked_sub(reserve_fund_amount).unwrap();
+        self.pending_buy_deposit = self.pending_buy_deposit.checked_sub(deposit.0).unwrap();
 
+        if is_promise_success() {
             self.total_fund_amount = self.total_fund_amount
                 .checked_add(reserve_fund_amount)
                 .unwrap();
@@ -317,39 +588,37 @@ impl Contract {
             // update circulating supply
             self.circulating_supply += amount.0;
 
-            // Transfer stable coin to treasury
-            ext_fungible_token::ext(self.stable_coin_id.clone())
-                .with_static_gas(GAS_FOR_FT_TRANSFER)
-                .with_attached_deposit(ONE_YOCTO)
-                .ft_transfer(
-                    self.treasury_id.clone(),
-                    U128::from(treasury_fee_amount),
-                    None,
-                );
+            let mut history = self.internal_account_history(&from);
+            history.paid_amount = history.paid_amount.checked_add(reserve.0).unwrap();
+            self.account_histories.insert(&from, &history);
+
+            let vote_weight = self.internal_vote_weight(&from, None);
+            self.internal_checkpoint_vote_weight(&from, vote_weight.checked_add(amount.0).unwrap());
+
+            // Referrer reward is taken from treasury fee
+            let referral_reward_amount = match referrer_id {
+                Some(referrer_id) => self.internal_credit_referral(&referrer_id, reserve.0, treasury_fee_amount),
+                None => 0,
+            };
+            let treasury_fee_amount = treasury_fee_amount.checked_sub(referral_reward_amount).unwrap();
+
+            // Accrue treasury fee or transfer it to treasury
+            if self.treasury_fee_accrual {
+                self.accrued_treasury_fee = self.accrued_treasury_fee.checked_add(treasury_fee_amount).unwrap();
+            } else if treasury_fee_amount > 0 {
+                self.internal_payout_transfer(self.treasury_id.clone(), treasury_fee_amount);
+            }
 
             let remain = deposit.0 - reserve.0;
             if remain > 0 {
-                ext_fungible_token::ext(self.stable_coin_id.clone())
-                    .with_static_gas(GAS_FOR_FT_TRANSFER)
-                    .with_attached_deposit(ONE_YOCTO)
-                    .ft_transfer(
-                        from.clone(),
-                        U128(remain),
-                        None,
-                    );
+                self.internal_payout_transfer(from.clone(), remain);
             }
 
             log!("Buy {} {} {}", from, amount.0, reserve.0);
             true
         } else {
-            ext_fungible_token::ext(self.stable_coin_id.clone())
-                .with_static_gas(GAS_FOR_FT_TRANSFER)
-                .with_attached_deposit(ONE_YOCTO)
-                .ft_transfer(
-                    from.clone(),
-                    deposit,
-                    None,
-                );
+            self.internal_release_storage(&from, amount.0);
+            self.internal_payout_transfer(from, deposit.0);
             false
         }
     }
@@ -357,11 +626,19 @@ impl Contract {
     /// sell proxy token
     pub fn sell(&mut self, token_ids: Vec<TokenId>) -> Promise {
         self.assert_not_paused();
+        self.assert_holders_migrated();
+        self.assert_not_halted();
         self.assert_is_ongoing();
 
-        let cal_coin_amount = self.calculate_sell_proxy_token(token_ids.clone());
+        let reserve_value = self.internal_sell_reserve_value(&token_ids);
+        let sell_fee_amount = self.internal_sell_fee(reserve_value);
+        let cal_coin_amount = reserve_value.checked_sub(sell_fee_amount).unwrap();
         assert!(cal_coin_amount > 0, "{}", ERR09_INVALID_ACTION);
 
+        self.internal_lock_account(&env::predecessor_account_id());
+        self.pending_sell_refund = self.pending_sell_refund.checked_add(cal_coin_amount).unwrap();
+        self.pending_sell_amount = self.pending_sell_amount.checked_add(token_ids.len() as u128).unwrap();
+
         // Burn Proxy Token
         self.pt_burn(
             env::predecessor_account_id(),
@@ -379,18 +656,31 @@ impl Contract {
             )
             .then(
                 ext_self::ext(env::current_account_id())
-                .with_static_gas(Gas(5 * TGAS))
-                .on_sell(env::predecessor_account_id(), U128(cal_coin_amount), token_ids)
+                .with_static_gas(GAS_FOR_ON_SELL)
+                .on_sell(env::predecessor_account_id(), U128(cal_coin_amount), U128(sell_fee_amount), self.sell_fee_destination.clone(), token_ids)
             )
     }
 
     #[private]
-    pub fn on_sell(&mut self, from: AccountId, refund: U128, token_ids: Vec<TokenId>) -> bool {
+    pub fn on_sell(&mut self, from: AccountId, refund: U128, fee: U128, fee_destination: SellFeeDestination, token_ids: Vec<TokenId>) -> bool {
+        self.internal_unlock_account(&from);
+        self.pending_sell_refund = self.pending_sell_refund.checked_sub(refund.0).unwrap();
+        self.pending_sell_amount = self.pending_sell_amount.checked_sub(token_ids.len() as u128).unwrap();
+
         if is_promise_success() {
             self.total_fund_amount = self.total_fund_amount.checked_sub(refund.0).unwrap();
+            self.internal_distribute_sell_fee(fee.0, fee_destination);
             // update circulating supply
             self.circulating_supply -= token_ids.len() as u128;
 
+            let mut history = self.internal_account_history(&from);
+            history.refunded_amount = history.refunded_amount.checked_add(refund.0).unwrap();
+            self.account_histories.insert(&from, &history);
+
+            let vote_weight = self.internal_vote_weight(&from, None);
+            self.internal_checkpoint_vote_weight(&from, vote_weight.saturating_sub(token_ids.len() as u128));
+            self.internal_release_storage(&from, token_ids.len() as u128);
+
             log!("Sell {} [{}] {}", from, token_ids.join(","), refund.0);
             true
         } else {
@@ -400,13 +690,30 @@ impl Contract {
         }
     }
 
-    /// convert proxy token to real token
+    /// convert proxy token to real token,
+    /// attached deposit registers caller on fungible project token
+    #[payable]
     pub fn convert(&mut self, token_ids: Vec<TokenId>) -> Promise {
         self.assert_not_paused();
+        self.assert_holders_migrated();
+        self.assert_not_halted();
         self.assert_is_after_buffer_period();
-
-        let convert_project_token;
-        if self.closed_step >= ClosedStep::RemainProxy {
+        // escrow held token ids are taken by converted amount, so those conversions are serialized
+        let is_fixed_token_ids = !self.external_project
+            && self.closed_step >= ClosedStep::RemainProxy
+            && self.project_token_type == ProjectTokenType::NonFungible;
+        assert!(!is_fixed_token_ids || self.pending_convert_amount == 0, "{}", ERR022_CONVERSION_IN_PROGRESS);
+
+        self.internal_lock_account(&env::predecessor_account_id());
+        self.pending_convert_amount = self.pending_convert_amount.checked_add(token_ids.len() as u128).unwrap();
+
+        let mut convert_project_token;
+        let mut inventory_token_ids = None;
+        if self.external_project {
+            let (promise, inventory_ids) = self.internal_inventory_transfer(env::predecessor_account_id(), token_ids.len() as u128);
+            convert_project_token = promise;
+            inventory_token_ids = Some(inventory_ids);
+        } else if self.closed_step >= ClosedStep::RemainProxy {
             convert_project_token = self.internal_convert_transfer(env::predecessor_account_id(), token_ids.len() as u128)
         } else {
             convert_project_token = self.internal_project_token_mint(env::predecessor_account_id(), U128::from(token_ids.len() as u128))
@@ -414,77 +721,114 @@ impl Contract {
 
         self.pt_burn(env::predecessor_account_id(), token_ids.clone());
 
+        let deposit = env::attached_deposit();
+        if deposit > 0 && self.project_token_type == ProjectTokenType::Fungible {
+            convert_project_token = self
+                .internal_ft_storage_deposit(self.project_token_id.clone().unwrap(), env::predecessor_account_id(), deposit)
+                .then(convert_project_token);
+        }
+
         convert_project_token
             .then(
                 ext_self::ext(env::current_account_id())
                     .with_static_gas(Gas(5 * TGAS))
-                    .on_convert(env::predecessor_account_id(), token_ids)
+                    .on_convert(env::predecessor_account_id(), token_ids, inventory_token_ids)
             )
     }
 
     #[private]
-    pub fn on_convert(&mut self, from: AccountId, token_ids: Vec<TokenId>) -> bool {
+    pub fn on_convert(&mut self, from: AccountId, token_ids: Vec<TokenId>, inventory_token_ids: Option<Vec<TokenId>>) -> bool {
+        self.internal_unlock_account(&from);
+        self.pending_convert_amount = self.pending_convert_amount.checked_sub(token_ids.len() as u128).unwrap();
+
         if !is_promise_success() {
             self.revert_pt_burn(from.clone(), token_ids.clone());
+            if let Some(inventory_token_ids) = inventory_token_ids {
+                self.internal_revert_inventory_transfer(token_ids.len() as u128, inventory_token_ids);
+            }
             return false;
         }
         
         self.converted_amount = self.converted_amount.checked_add(token_ids.len() as u128).unwrap();
 
+        let mut history = self.internal_account_history(&from);
+        history.converted_amount = history.converted_amount.checked_add(token_ids.len() as u128).unwrap();
+        self.account_histories.insert(&from, &history);
+
+        // converted amount keeps voting weight
+        self.internal_release_storage(&from, token_ids.len() as u128);
+
         log!("Convert {} {}", from, token_ids.join(","));
         true
     }
 
-    /// claim fund
+    /// claim fund, attached deposit registers `to` on stable coin
+    #[payable]
     pub fn claim_fund(&mut self, to: AccountId, amount: U128) -> Promise {
-        self.assert_owner();
+        self.assert_role(Role::FundClaimer);
+        assert!(self.fund_release_mode == FundReleaseMode::Owner, "{}", ERR41_RELEASE_BY_PROPOSAL);
         self.assert_is_after_conversion_period();
 
-        let total_finder_fee = self.total_fund_amount.checked_mul(self.finder_fee as u128).unwrap().checked_div(FEE_DIVISOR as u128).unwrap();
-        let total_claimable_fund = self.total_fund_amount.checked_sub(total_finder_fee).unwrap();
-        assert!(amount.0 > 0 && (total_claimable_fund - self.claimed_fund_amount) >= amount.0, "{}", ERR010_INVALID_AMOUNT);
+        self.internal_claim_fund(to, amount, None)
+    }
 
-        ext_fungible_token::ext(self.stable_coin_id.clone())
-            .with_static_gas(Gas(5 * TGAS))
-            .with_attached_deposit(ONE_YOCTO)
-            .ft_transfer(
-                to,
-                U128::from(amount.0),
-                None,
-            )
+    pub(crate) fn internal_claim_fund(&mut self, to: AccountId, amount: U128, proposal_id: Option<u64>) -> Promise {
+        self.assert_not_halted();
+        assert!(amount.0 > 0 && self.internal_available_fund() >= amount.0, "{}", ERR010_INVALID_AMOUNT);
+        self.pending_fund_claim = self.pending_fund_claim.checked_add(amount.0).unwrap();
+
+        self.internal_stable_coin_transfer(to.clone(), amount.0, env::attached_deposit())
             .then(
                 ext_self::ext(env::current_account_id())
                         .with_static_gas(Gas(5 * TGAS))
-                        .on_claim_fund(amount)
+                        .on_claim_fund(to, amount, proposal_id)
             )
     }
 
     #[private]
-    pub fn on_claim_fund(&mut self, amount: U128) -> bool {
+    pub fn on_claim_fund(&mut self, to: AccountId, amount: U128, proposal_id: Option<u64>) -> bool {
+        self.pending_fund_claim = self.pending_fund_claim.checked_sub(amount.0).unwrap();
+
         if is_promise_success() {
-            self.claimed_fund_amount = self.claimed_fund_amount + amount.0;
+            self.claimed_fund_amount += amount.0;
+            if let Some(proposal_id) = proposal_id {
+                EscrowEvent::FundReleaseExecuted { proposal_id, amount }.emit();
+            }
             return true;
         }
 
+        if let Some(proposal_id) = proposal_id {
+            // keep proposal approved to retry with `execute_fund_release`
+            let mut proposal = self.fund_release_proposals.get(&proposal_id).unwrap();
+            proposal.status = ProposalStatus::Approved;
+            self.fund_release_proposals.insert(&proposal_id, &proposal);
+            self.approved_fund_release_amount = self.approved_fund_release_amount.checked_add(amount.0).unwrap();
+            EscrowEvent::FundReleaseFailed { proposal_id, amount }.emit();
+        } else {
+            // claimed fund is kept for `to` to withdraw with `withdraw_pending`
+            self.claimed_fund_amount += amount.0;
+            self.internal_add_pending_payout(&to, amount.0);
+        }
+
         false
     }
 
-    /// claim finder fee
+    /// claim finder fee, attached deposit registers finder on stable coin
+    #[payable]
     pub fn claim_finder_fee(&mut self, amount: U128) -> Promise {
-        self.assert_owner();
+        self.assert_role(Role::FundClaimer);
+        self.assert_not_halted();
         self.assert_is_after_conversion_period();
 
-        let total_finder_fee = self.total_fund_amount.checked_mul(self.finder_fee as u128).unwrap().checked_div(FEE_DIVISOR as u128).unwrap();
-        assert!(amount.0 > 0 && (total_finder_fee - self.claimed_finder_fee) >= amount.0, "{}", ERR010_INVALID_AMOUNT);
+        let total_finder_fee = self.internal_total_finder_fee();
+        assert!(
+            amount.0 > 0 && (total_finder_fee - self.claimed_finder_fee - self.pending_finder_fee_claim) >= amount.0,
+            "{}",
+            ERR010_INVALID_AMOUNT
+        );
+        self.pending_finder_fee_claim = self.pending_finder_fee_claim.checked_add(amount.0).unwrap();
 
-       ext_fungible_token::ext(self.stable_coin_id.clone())
-            .with_static_gas(Gas(5 * TGAS))
-            .with_attached_deposit(ONE_YOCTO)
-            .ft_transfer(
-                self.finder_id.clone().unwrap(),
-                U128::from(amount.0),
-                None,
-            )
+        self.internal_stable_coin_transfer(self.finder_id.clone().unwrap(), amount.0, env::attached_deposit())
            .then(
                 ext_self::ext(env::current_account_id())
                     .with_static_gas(Gas(5 * TGAS))
@@ -494,17 +838,23 @@ impl Contract {
 
     #[private]
     pub fn on_claim_finder_fee(&mut self, amount: U128) -> bool {
+        self.pending_finder_fee_claim = self.pending_finder_fee_claim.checked_sub(amount.0).unwrap();
+
+        self.claimed_finder_fee += amount.0;
         if is_promise_success() {
-            self.claimed_finder_fee = self.claimed_finder_fee + amount.0;
             return true;
         }
 
+        // claimed finder fee is kept for finder to withdraw with `withdraw_pending`
+        self.internal_add_pending_payout(&self.finder_id.clone().unwrap(), amount.0);
         false
     }
 
     /// close project 1-step pre-mint
     pub fn close_project(&mut self) -> PromiseOrValue<bool> {
-        self.assert_owner();
+        self.assert_role(Role::Admin);
+        self.assert_not_halted();
+        self.assert_holders_migrated();
         assert!(
             self.start_timestamp == 0 ||
                 (self.tp_timestamp > 0 &&
@@ -512,35 +862,61 @@ impl Contract {
             "{}",
             ERR011_NOT_AVAILABLE_TO_CLOSE
         );
+        // token id offsets and remaining proxies are taken from converted amount
+        assert_eq!(self.pending_convert_amount, 0, "{}", ERR022_CONVERSION_IN_PROGRESS);
 
+        let mut inventory_token_ids = None;
         let close_promise: Option<Promise> = match self.closed_step {
             ClosedStep::None => {
                 if self.pre_mint_amount > 0 {
                     let token_ids = (0..self.pre_mint_amount - 1).enumerate().map(|(_, token_id)| { token_id.to_string() }).collect();
-                    self.pt_burn(self.owner_id.clone(), token_ids);
-
-                    Some(self.internal_project_token_mint(self.owner_id.clone(), U128::from(self.pre_mint_amount)))
+                    self.pt_burn(self.internal_owner_id(), token_ids);
+
+                    if self.external_project {
+                        let (promise, inventory_ids) = self.internal_inventory_transfer(self.internal_owner_id(), self.pre_mint_amount);
+                        inventory_token_ids = Some(inventory_ids);
+                        Some(promise)
+                    } else {
+                        // vesting allocation is held by escrow and released with `claim_vested`
+                        let receiver_id = if self.pre_mint_vesting.is_some() {
+                            self.pre_mint_token_offset = self.converted_amount + self.internal_claimed_allocation();
+                            env::current_account_id()
+                        } else {
+                            self.internal_owner_id()
+                        };
+                        Some(self.internal_project_token_mint(receiver_id, U128::from(self.pre_mint_amount)))
+                    }
                 } else {
                     None
                 }
             }
             ClosedStep::PreMint => {
                 let remain_proxys = self.circulating_supply.checked_sub(self.converted_amount).unwrap();
-                if remain_proxys > 0 {
-                    Some(self.internal_project_token_mint(env::current_account_id(), U128::from(remain_proxys)))
+                self.remain_proxy_token_offset = self.pre_mint_amount + self.internal_claimed_allocation();
+                // unclaimed allocations are minted after remaining proxies and transferred by `claim_allocation`,
+                // so the project owner can take project token ownership before allocations are vested
+                let unclaimed_allocation = self.internal_total_allocation().checked_sub(self.internal_claimed_allocation()).unwrap();
+                // remaining proxies of external project are converted from inventory
+                if remain_proxys + unclaimed_allocation > 0 && !self.external_project {
+                    self.allocation_token_offset = Some(self.pre_mint_amount + self.circulating_supply);
+                    Some(self.internal_project_token_mint(env::current_account_id(), U128::from(remain_proxys + unclaimed_allocation)))
                 } else {
                     None
                 }
             }
+            ClosedStep::RemainProxy if self.external_project => None,
             ClosedStep::RemainProxy => {
+                // project owner should accept ownership of project token with `own_accept_owner`
                 Some(match self.project_token_type {
                         ProjectTokenType::Fungible =>
                             ext_fungible_token::ext(self.project_token_id.clone().unwrap())
                                 .with_static_gas(Gas(5 * TGAS))
-                                .set_owner(self.owner_id.clone()),
+                                .with_attached_deposit(1)
+                                .own_propose_owner(Some(self.internal_owner_id())),
                         ProjectTokenType::NonFungible => ext_nft_collection::ext(self.project_token_id.clone().unwrap())
                             .with_static_gas(Gas(5 * TGAS))
-                            .set_owner(self.owner_id.clone())
+                            .with_attached_deposit(1)
+                            .own_propose_owner(Some(self.internal_owner_id()))
                 })
             }
             ClosedStep::TransferOwnership => {
@@ -557,26 +933,106 @@ impl Contract {
                     .then(
                         ext_self::ext(env::current_account_id())
                             .with_static_gas(Gas(5 * TGAS))
-                            .on_close_project()
+                            .on_close_project(inventory_token_ids)
                     )
             )
         }
     }
 
-    pub fn on_close_project(&mut self) -> bool {
+    pub fn on_close_project(&mut self, inventory_token_ids: Option<Vec<TokenId>>) -> bool {
         if is_promise_success() {
+            if self.closed_step == ClosedStep::None && self.pre_mint_vesting.is_some() {
+                self.pre_mint_vesting_start = Some(env::block_timestamp());
+            }
             self.closed_step = self.closed_step.increase();
             return true;
         }
 
+        if self.closed_step == ClosedStep::PreMint {
+            self.allocation_token_offset = None;
+        }
         if self.closed_step == ClosedStep::None {
             let token_ids = (0..self.pre_mint_amount - 1).enumerate().map(|(_, token_id)| { token_id.to_string() }).collect();
-            self.revert_pt_burn(self.owner_id.clone(), token_ids);
+            self.revert_pt_burn(self.internal_owner_id(), token_ids);
+            if let Some(inventory_token_ids) = inventory_token_ids {
+                self.internal_revert_inventory_transfer(self.pre_mint_amount, inventory_token_ids);
+            }
         }
 
         return false;
     }
 
+    /// Snapshot config overwritten by activation and lock activation until `on_activate`
+    pub(crate) fn internal_begin_activation(&mut self, storage_cost: Balance) {
+        assert_eq!(self.start_timestamp, 0, "{}", ERR15_ALREADY_ACTIVATED);
+        assert!(self.pending_activation.is_none(), "{}", ERR019_ACTIVATION_IN_PROGRESS);
+        let deposit = env::attached_deposit();
+        assert!(deposit >= storage_cost, "{}", ERR020_INSUFFICIENT_DEPOSIT);
+
+        self.pending_activation = Some(PendingActivation {
+            caller_id: env::predecessor_account_id(),
+            deposit,
+            storage_cost,
+            finder_id: self.finder_id.clone(),
+            fund_threshold: self.fund_threshold,
+            buffer_period: self.buffer_period,
+            conversion_period: self.conversion_period,
+            project_token_type: self.project_token_type.clone(),
+            pt_max_supply: self.pt_max_supply,
+        });
+    }
+
+    /// Project token sub-account from explicit prefix or project name
+    pub(crate) fn internal_project_token_account(&self, token_prefix: Option<String>) -> AccountId {
+        let prefix = token_prefix.unwrap_or_else(|| {
+            let mut token_suffix = self.name.clone().to_lowercase();
+            token_suffix.retain(|c| !c.is_whitespace());
+            token_suffix
+        });
+        assert!(!prefix.is_empty() && !prefix.contains('.'), "{}", ERR016_INVALID_PROJECT_TOKEN_ID);
+
+        format!("{}.{}", prefix, env::current_account_id())
+            .parse()
+            .unwrap_or_else(|_| env::panic_str(ERR016_INVALID_PROJECT_TOKEN_ID))
+    }
+
+    pub(crate) fn internal_account_history(&self, account_id: &AccountId) -> AccountHistory {
+        self.account_histories.get(account_id).unwrap_or_default()
+    }
+
+    pub(crate) fn internal_total_finder_fee(&self) -> Balance {
+        self.total_fund_amount.checked_mul(self.finder_fee as u128).unwrap().checked_div(FEE_DIVISOR as u128).unwrap()
+    }
+
+    pub(crate) fn internal_total_claimable_fund(&self) -> Balance {
+        self.total_fund_amount.checked_sub(self.internal_total_finder_fee()).unwrap()
+    }
+
+    /// Claimable fund unlocked by milestones, all fund if no milestone
+    pub(crate) fn internal_unlocked_fund(&self) -> Balance {
+        let total_claimable_fund = self.internal_total_claimable_fund();
+        if self.milestones.is_empty() {
+            return total_claimable_fund;
+        }
+
+        let unlocked_percent: u32 = self.milestones
+            .iter()
+            .filter(|milestone| milestone.is_unlocked(env::block_timestamp()))
+            .map(|milestone| milestone.percent)
+            .sum();
+        total_claimable_fund
+            .checked_mul(unlocked_percent as u128)
+            .unwrap()
+            .checked_div(FEE_DIVISOR as u128)
+            .unwrap()
+    }
+
+    /// Unlocked fund not claimed, in flight or reserved by approved fund release proposals
+    pub(crate) fn internal_available_fund(&self) -> Balance {
+        self.internal_unlocked_fund()
+            .saturating_sub(self.claimed_fund_amount + self.pending_fund_claim + self.approved_fund_release_amount)
+    }
+
     pub fn internal_project_token_mint(&mut self, to: AccountId, amount: U128) -> Promise {
         match self.project_token_type {
             ProjectTokenType::NonFungible => ext_nft_collection::ext(self.project_token_id.clone().unwrap())
@@ -599,7 +1055,7 @@ impl Contract {
         match self.project_token_type {
             ProjectTokenType::NonFungible => {
                 let token_ids: Vec<TokenId> = (0..amount-1).enumerate().map(|(_, id)| {
-                    return (self.pre_mint_amount + self.converted_amount + id).to_string();
+                    return (self.remain_proxy_token_offset + self.converted_amount + id).to_string();
                 }).collect();
                 ext_nft_collection::ext(self.project_token_id.clone().unwrap())
                     .with_static_gas(Gas(5 * TGAS))
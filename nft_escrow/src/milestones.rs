@@ -0,0 +1,83 @@
+use crate::*;
+
+const MAX_MILESTONES: usize = 20;
+
+#[near_bindgen]
+impl Contract {
+    /// Set milestones unlocking raised fund before activation, caller should be admin.
+    /// Percents of all milestones should sum up to 100%.
+    pub fn set_milestones(&mut self, milestones: Vec<MilestoneArgs>, verifier_id: Option<AccountId>) {
+        self.assert_role(Role::Admin);
+        assert_eq!(self.start_timestamp, 0, "{}", ERR15_ALREADY_ACTIVATED);
+        assert!(milestones.len() <= MAX_MILESTONES, "{}", ERR50_INVALID_MILESTONES);
+        assert!(
+            milestones.is_empty() || milestones.iter().map(|milestone| milestone.percent).sum::<u32>() == FEE_DIVISOR,
+            "{}",
+            ERR50_INVALID_MILESTONES
+        );
+        milestones.iter().for_each(|milestone| {
+            assert!(milestone.percent > 0, "{}", ERR50_INVALID_MILESTONES);
+            assert!(milestone.unlock_timestamp.is_some() || milestone.requires_approval, "{}", ERR50_INVALID_MILESTONES);
+            assert!(!milestone.requires_approval || verifier_id.is_some(), "{}", ERR50_INVALID_MILESTONES);
+        });
+
+        self.milestones = milestones
+            .into_iter()
+            .map(|milestone| Milestone {
+                title: milestone.title,
+                percent: milestone.percent,
+                unlock_timestamp: milestone.unlock_timestamp,
+                requires_approval: milestone.requires_approval,
+                status: MilestoneStatus::Pending,
+                evidence: None,
+            })
+            .collect();
+        self.milestone_verifier_id = verifier_id;
+
+        log!("Milestones {}", self.milestones.len());
+    }
+
+    /// Submit milestone for verifier approval, caller should be admin
+    pub fn submit_milestone(&mut self, index: u32, evidence: String) {
+        self.assert_role(Role::Admin);
+        let milestone = self.milestones.get_mut(index as usize).unwrap_or_else(|| env::panic_str(ERR51_NO_MILESTONE));
+        assert!(
+            milestone.requires_approval && milestone.status != MilestoneStatus::Approved,
+            "{}",
+            ERR52_INVALID_MILESTONE_STATUS
+        );
+
+        milestone.status = MilestoneStatus::Submitted;
+        milestone.evidence = Some(evidence.clone());
+
+        EscrowEvent::MilestoneSubmitted { index, evidence }.emit();
+    }
+
+    /// Approve submitted milestone, caller should be milestone verifier
+    pub fn approve_milestone(&mut self, index: u32) {
+        let verifier_id = env::predecessor_account_id();
+        assert_eq!(Some(&verifier_id), self.milestone_verifier_id.as_ref(), "{}", ERR53_NOT_VERIFIER);
+        let milestone = self.milestones.get_mut(index as usize).unwrap_or_else(|| env::panic_str(ERR51_NO_MILESTONE));
+        assert_eq!(milestone.status, MilestoneStatus::Submitted, "{}", ERR52_INVALID_MILESTONE_STATUS);
+
+        milestone.status = MilestoneStatus::Approved;
+
+        EscrowEvent::MilestoneApproved { index, verifier_id }.emit();
+    }
+
+    pub fn get_milestones(&self) -> Vec<Milestone> { self.milestones.clone() }
+
+    pub fn get_milestone_verifier_id(&self) -> Option<AccountId> { self.milestone_verifier_id.clone() }
+
+    pub fn get_fund_lock_info(&self) -> FundLockInfo {
+        let total_fund = self.internal_total_claimable_fund();
+        let unlocked_fund = self.internal_unlocked_fund();
+
+        FundLockInfo {
+            total_fund: U128(total_fund),
+            unlocked_fund: U128(unlocked_fund),
+            locked_fund: U128(total_fund.checked_sub(unlocked_fund).unwrap()),
+            claimed_fund: U128(self.claimed_fund_amount),
+        }
+    }
+}
@@ -1,22 +1,50 @@
+use near_contract_tools::owner::{Owner, OwnerExternal};
+use near_contract_tools::slot::Slot;
+use near_sdk::assert_one_yocto;
 use crate::*;
 
+impl Owner for Contract {
+    fn root() -> Slot<()> {
+        Slot::root(b"~o" as &[u8])
+    }
+}
+
+/// `Owner` component without renounce, closing and vesting are done for owner
 #[near_bindgen]
-impl Contract {
-    pub fn get_owner(&self) -> AccountId {
-        self.owner_id.clone()
+impl OwnerExternal for Contract {
+    fn own_get_owner(&self) -> Option<AccountId> {
+        Self::slot_owner().read()
+    }
+
+    fn own_get_proposed_owner(&self) -> Option<AccountId> {
+        Self::slot_proposed_owner().read()
+    }
+
+    #[payable]
+    fn own_renounce_owner(&mut self) {
+        env::panic_str(ERR22_RENOUNCE_NOT_ALLOWED)
+    }
+
+    #[payable]
+    fn own_propose_owner(&mut self, account_id: Option<AccountId>) {
+        assert_one_yocto();
+        self.propose_owner(account_id);
     }
 
-    pub fn set_owner(&mut self, owner_id: AccountId) {
-        self.assert_owner();
-        self.owner_id = owner_id.into();
+    #[payable]
+    fn own_accept_owner(&mut self) {
+        assert_one_yocto();
+        self.accept_owner();
+    }
+}
+
+impl Contract {
+    /// Owner of escrow, ownership is transferred with `own_propose_owner` and `own_accept_owner`
+    pub(crate) fn internal_owner_id(&self) -> AccountId {
+        Self::slot_owner().read().unwrap_or_else(|| env::panic_str(ERR21_NO_OWNER))
     }
 
-    pub(crate) fn assert_owner(&self) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.owner_id,
-            "{}",
-            ERR20_NOT_ALLOW
-        );
+    pub(crate) fn is_owner(&self, account_id: &AccountId) -> bool {
+        Self::slot_owner().read().as_ref() == Some(account_id)
     }
 }
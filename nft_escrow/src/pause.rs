@@ -6,7 +6,7 @@ impl Contract {
     #[payable]
     pub fn pause_contract(&mut self) {
         assert_one_yocto();
-        self.assert_owner();
+        self.assert_role(Role::Pauser);
 
         if self.state == RunningState::Running {
             env::log_str(format!("Contract paused by {}", env::predecessor_account_id()).as_str());
@@ -19,7 +19,7 @@ impl Contract {
     #[payable]
     pub fn resume_contract(&mut self) {
         assert_one_yocto();
-        self.assert_owner();
+        self.assert_role(Role::Pauser);
 
         if self.state == RunningState::Paused {
             env::log_str(format!("Contract resumed by {}", env::predecessor_account_id()).as_str());
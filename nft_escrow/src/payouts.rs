@@ -0,0 +1,164 @@
+use near_contract_standards::storage_management::StorageBalance;
+use near_sdk::PromiseError;
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Retry stable coin transfers to caller which failed before.
+    /// Attached deposit registers caller on stable coin, attach it only when caller is not registered.
+    #[payable]
+    pub fn withdraw_pending(&mut self) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let amount = self.pending_payouts.remove(&account_id).unwrap_or(0);
+        assert!(amount > 0, "{}", ERR029_NO_PENDING_PAYOUT);
+        self.total_pending_payout = self.total_pending_payout.checked_sub(amount).unwrap();
+        self.pending_payout_transfer = self.pending_payout_transfer.checked_add(amount).unwrap();
+
+        self.internal_stable_coin_transfer(account_id.clone(), amount, env::attached_deposit())
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .on_withdraw_pending(account_id, U128(amount))
+            )
+    }
+
+    #[private]
+    pub fn on_withdraw_pending(&mut self, account_id: AccountId, amount: U128) -> bool {
+        self.pending_payout_transfer = self.pending_payout_transfer.checked_sub(amount.0).unwrap();
+        if !is_promise_success() {
+            self.internal_add_pending_payout(&account_id, amount.0);
+            return false;
+        }
+
+        log!("Withdraw pending {} {}", account_id, amount.0);
+        true
+    }
+
+    /// Record stable coin transfer failed in `on_buy` as pending payout of receiver
+    #[private]
+    pub fn on_payout_transfer(&mut self, account_id: AccountId, amount: U128) -> bool {
+        self.pending_payout_transfer = self.pending_payout_transfer.checked_sub(amount.0).unwrap();
+        if !is_promise_success() {
+            self.internal_add_pending_payout(&account_id, amount.0);
+            return false;
+        }
+
+        true
+    }
+
+    /// Register account on fungible token unless it is already registered, deposit is refunded otherwise
+    #[private]
+    pub fn on_ft_storage_balance_of(
+        &mut self,
+        token_id: AccountId,
+        account_id: AccountId,
+        deposit: U128,
+        refund_id: AccountId,
+        #[callback_result] storage_balance: Result<Option<StorageBalance>, PromiseError>,
+    ) -> PromiseOrValue<bool> {
+        if let Ok(Some(_)) = storage_balance {
+            Promise::new(refund_id).transfer(deposit.0);
+            return PromiseOrValue::Value(false);
+        }
+
+        ext_fungible_token::ext(token_id)
+            .with_static_gas(Gas(5 * TGAS))
+            .with_attached_deposit(deposit.0)
+            .storage_deposit(Some(account_id), Some(true))
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .on_ft_storage_deposit(deposit, refund_id)
+            )
+            .into()
+    }
+
+    /// Refund deposit above registration cost, failed registration returned whole deposit to escrow
+    #[private]
+    pub fn on_ft_storage_deposit(
+        &mut self,
+        deposit: U128,
+        refund_id: AccountId,
+        #[callback_result] storage_balance: Result<StorageBalance, PromiseError>,
+    ) -> bool {
+        let (refund, registered) = match storage_balance {
+            Ok(storage_balance) => (deposit.0.saturating_sub(storage_balance.total.0), true),
+            Err(_) => (deposit.0, false),
+        };
+        if refund > 0 {
+            Promise::new(refund_id).transfer(refund);
+        }
+        registered
+    }
+
+    /// Stable coin of failed transfers owed to account
+    pub fn get_pending_payout(&self, account_id: AccountId) -> U128 {
+        U128(self.pending_payouts.get(&account_id).unwrap_or(0))
+    }
+
+    /// Pending payouts per account
+    pub fn get_pending_payouts(&self, from_index: Option<u64>, limit: Option<u64>) -> Vec<PendingPayout> {
+        let from_index = from_index.unwrap_or(0);
+        let limit = limit.unwrap_or(self.pending_payouts.len());
+        self.pending_payouts
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|(account_id, amount)| PendingPayout { account_id, amount: U128(amount) })
+            .collect()
+    }
+
+    pub fn get_total_pending_payout(&self) -> U128 { U128(self.total_pending_payout) }
+
+    pub(crate) fn internal_add_pending_payout(&mut self, account_id: &AccountId, amount: Balance) {
+        let pending = self.pending_payouts.get(account_id).unwrap_or(0);
+        self.pending_payouts.insert(account_id, &pending.checked_add(amount).unwrap());
+        self.total_pending_payout = self.total_pending_payout.checked_add(amount).unwrap();
+
+        log!("Pending payout {} {}", account_id, amount);
+    }
+
+    /// Register account on fungible token with `deposit`, unused deposit is refunded to caller
+    pub(crate) fn internal_ft_storage_deposit(&self, token_id: AccountId, account_id: AccountId, deposit: Balance) -> Promise {
+        ext_fungible_token::ext(token_id.clone())
+            .with_static_gas(Gas(5 * TGAS))
+            .storage_balance_of(account_id.clone())
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(Gas(20 * TGAS))
+                    .on_ft_storage_balance_of(token_id, account_id, U128(deposit), env::predecessor_account_id())
+            )
+    }
+
+    /// Transfer stable coin, failed transfer is kept as pending payout of receiver
+    pub(crate) fn internal_payout_transfer(&mut self, receiver_id: AccountId, amount: Balance) -> Promise {
+        self.pending_payout_transfer = self.pending_payout_transfer.checked_add(amount).unwrap();
+        ext_fungible_token::ext(self.stable_coin_id.clone())
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .with_attached_deposit(ONE_YOCTO)
+            .ft_transfer(
+                receiver_id.clone(),
+                U128(amount),
+                None,
+            )
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .on_payout_transfer(receiver_id, U128(amount))
+            )
+    }
+
+    /// Transfer stable coin, registering receiver first if `deposit` is attached
+    pub(crate) fn internal_stable_coin_transfer(&self, receiver_id: AccountId, amount: Balance, deposit: Balance) -> Promise {
+        let transfer_promise = ext_fungible_token::ext(self.stable_coin_id.clone())
+            .with_static_gas(Gas(5 * TGAS))
+            .with_attached_deposit(ONE_YOCTO);
+
+        if deposit > 0 {
+            self.internal_ft_storage_deposit(self.stable_coin_id.clone(), receiver_id.clone(), deposit)
+                .then(transfer_promise.ft_transfer(receiver_id, U128(amount), None))
+        } else {
+            transfer_promise.ft_transfer(receiver_id, U128(amount), None)
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Amounts reserved by operations waiting for their callbacks
+    pub fn get_pending_operations(&self) -> PendingOperations {
+        PendingOperations {
+            buy_fund: U128(self.pending_buy_fund),
+            buy_deposit: U128(self.pending_buy_deposit),
+            payout_transfer: U128(self.pending_payout_transfer),
+            sell_refund: U128(self.pending_sell_refund),
+            sell_amount: U128(self.pending_sell_amount),
+            convert_amount: U128(self.pending_convert_amount),
+            fund_claim: U128(self.pending_fund_claim),
+            finder_fee_claim: U128(self.pending_finder_fee_claim),
+        }
+    }
+
+    /// Whether account has sell, convert or refund waiting for its callback
+    pub fn is_account_pending(&self, account_id: AccountId) -> bool {
+        self.pending_accounts.contains(&account_id)
+    }
+
+    /// Lock account until callback of its operation, one operation per account in flight
+    pub(crate) fn internal_lock_account(&mut self, account_id: &AccountId) {
+        assert!(self.pending_accounts.insert(account_id), "{}", ERR021_OPERATION_IN_PROGRESS);
+    }
+
+    pub(crate) fn internal_unlock_account(&mut self, account_id: &AccountId) {
+        self.pending_accounts.remove(account_id);
+    }
+}
@@ -41,6 +41,7 @@ impl Contract {
                 let mut balances = self.pt_balances_per_token.get(&token_id).unwrap();
                 balances.insert(&receiver_id, &new);
             }
+            self.internal_add_pt_to_owner(&receiver_id, &token_id);
 
             token_ids.push(token_id);
             i += 1;
@@ -65,6 +66,9 @@ impl Contract {
             if let Some(new) = balance.checked_sub(1) {
                 let mut balances = self.pt_balances_per_token.get(token_id).unwrap();
                 balances.insert(&from_id, &new);
+                if new == 0 {
+                    self.internal_remove_pt_from_owner(&from_id, token_id);
+                }
                 self.pt_total_supply.insert(
                     token_id,
                     &self
@@ -94,6 +98,7 @@ impl Contract {
             let balance = self.internal_unwrap_balance_of(token_id, &from_id);
             let mut balances = self.pt_balances_per_token.get(token_id).unwrap();
             balances.insert(&from_id, &(balance + 1));
+            self.internal_add_pt_to_owner(&from_id, token_id);
             self.pt_total_supply.insert(
                 token_id,
                 &self
@@ -108,6 +113,41 @@ impl Contract {
         self.pt_all_total_supply = self.pt_all_total_supply.checked_add(token_ids.len().try_into().unwrap()).unwrap();
     }
 
+    pub(crate) fn internal_add_pt_to_owner(&mut self, owner_id: &AccountId, token_id: &TokenId) {
+        let mut token_ids = self.pt_tokens_per_owner.get(owner_id).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::TokensPerOwnerInner {
+                account_hash: env::sha256(owner_id.as_bytes()),
+            })
+        });
+        token_ids.insert(token_id);
+        self.pt_tokens_per_owner.insert(owner_id, &token_ids);
+    }
+
+    pub(crate) fn internal_remove_pt_from_owner(&mut self, owner_id: &AccountId, token_id: &TokenId) {
+        if let Some(mut token_ids) = self.pt_tokens_per_owner.get(owner_id) {
+            token_ids.remove(token_id);
+            if token_ids.is_empty() {
+                self.pt_tokens_per_owner.remove(owner_id);
+            } else {
+                self.pt_tokens_per_owner.insert(owner_id, &token_ids);
+            }
+        }
+    }
+
+    /// Pre-minted tokens of owner are not weighted until closing burns them
+    pub(crate) fn internal_is_pre_mint_token(&self, account_id: &AccountId, token_id: &TokenId) -> bool {
+        self.closed_step == ClosedStep::None
+            && self.is_owner(account_id)
+            && token_id.parse::<u128>().is_ok_and(|id| id < self.pre_mint_amount)
+    }
+
+    pub fn pt_tokens_for_owner(&self, account_id: AccountId) -> Vec<TokenId> {
+        self.pt_tokens_per_owner
+            .get(&account_id)
+            .map(|token_ids| token_ids.to_vec())
+            .unwrap_or_default()
+    }
+
     pub fn pt_token(&self, token_id: TokenId) -> Option<Token> {
         let metadata = ProxyTokenMetadata {
             title: Some(token_id.clone()),
@@ -0,0 +1,147 @@
+use near_sdk::PromiseError;
+use near_sdk::assert_one_yocto;
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Transfer fungible token sent to escrow by mistake to caller, caller should be owner or treasury.
+    /// Stable coin is recovered only above the balance escrow should hold,
+    /// project token only from external inventory above remaining proxy tokens.
+    #[payable]
+    pub fn recover_ft(&mut self, token_id: AccountId, amount: U128) -> Promise {
+        assert_one_yocto();
+        let receiver_id = self.assert_recoverer();
+        assert!(amount.0 > 0, "{}", ERR010_INVALID_AMOUNT);
+
+        if token_id == self.stable_coin_id {
+            return ext_fungible_token::ext(token_id)
+                .with_static_gas(Gas(5 * TGAS))
+                .ft_balance_of(env::current_account_id())
+                .then(
+                    ext_self::ext(env::current_account_id())
+                        .with_static_gas(Gas(20 * TGAS))
+                        .on_recover_stable_coin(receiver_id, amount)
+                );
+        }
+
+        let is_project_token = Some(&token_id) == self.project_token_id.as_ref();
+        if is_project_token {
+            assert!(self.project_token_type == ProjectTokenType::Fungible, "{}", ERR025_RESERVE_TOKEN);
+            assert!(amount.0 <= self.internal_recoverable_inventory_amount(), "{}", ERR025_RESERVE_TOKEN);
+            self.inventory_amount -= amount.0;
+        }
+
+        log!("Recover {} {} {}", token_id, receiver_id, amount.0);
+        let promise = ext_fungible_token::ext(token_id)
+            .with_static_gas(Gas(5 * TGAS))
+            .with_attached_deposit(ONE_YOCTO)
+            .ft_transfer(
+                receiver_id,
+                amount,
+                None,
+            );
+        if is_project_token {
+            promise.then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .on_recover_inventory(amount, vec![])
+            )
+        } else {
+            promise
+        }
+    }
+
+    /// Transfer non-fungible token sent to escrow by mistake to caller, caller should be owner or treasury.
+    /// Project token is recovered only from external project, inventory only above remaining proxy tokens.
+    #[payable]
+    pub fn recover_nft(&mut self, contract_id: AccountId, token_id: TokenId) -> Promise {
+        assert_one_yocto();
+        let receiver_id = self.assert_recoverer();
+        assert!(contract_id != self.stable_coin_id, "{}", ERR025_RESERVE_TOKEN);
+
+        let mut is_inventory_token = false;
+        if Some(&contract_id) == self.project_token_id.as_ref() {
+            // project token ids held for vesting and conversion are not tracked unless external
+            assert!(self.external_project && self.project_token_type == ProjectTokenType::NonFungible, "{}", ERR025_RESERVE_TOKEN);
+            if let Some(index) = self.inventory_token_ids.iter().position(|inventory_token_id| inventory_token_id == token_id) {
+                assert!(self.internal_recoverable_inventory_amount() > 0, "{}", ERR025_RESERVE_TOKEN);
+                self.inventory_token_ids.swap_remove(index as u64);
+                is_inventory_token = true;
+            }
+        }
+
+        log!("Recover {} {} {}", contract_id, receiver_id, token_id);
+        let promise = ext_nft_collection::ext(contract_id)
+            .with_static_gas(Gas(5 * TGAS))
+            .with_attached_deposit(ONE_YOCTO)
+            .nft_transfer(
+                receiver_id,
+                token_id.clone(),
+                None,
+                None,
+            );
+        if is_inventory_token {
+            promise.then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .on_recover_inventory(U128(1), vec![token_id])
+            )
+        } else {
+            promise
+        }
+    }
+
+    #[private]
+    pub fn on_recover_stable_coin(&mut self, receiver_id: AccountId, amount: U128, #[callback_result] balance: Result<U128, PromiseError>) -> Promise {
+        let balance = balance.unwrap_or_else(|_| env::panic_str(ERR023_BALANCE_QUERY_FAILED));
+        assert!(amount.0 <= self.internal_recoverable_stable_coin_amount(balance.0), "{}", ERR025_RESERVE_TOKEN);
+
+        log!("Recover {} {} {}", self.stable_coin_id, receiver_id, amount.0);
+        ext_fungible_token::ext(self.stable_coin_id.clone())
+            .with_static_gas(Gas(5 * TGAS))
+            .with_attached_deposit(ONE_YOCTO)
+            .ft_transfer(
+                receiver_id,
+                amount,
+                None,
+            )
+    }
+
+    #[private]
+    pub fn on_recover_inventory(&mut self, amount: U128, token_ids: Vec<TokenId>) -> bool {
+        if !is_promise_success() {
+            self.internal_revert_inventory_transfer(amount.0, token_ids);
+            return false;
+        }
+        true
+    }
+
+    pub(crate) fn assert_recoverer(&self) -> AccountId {
+        let caller_id = env::predecessor_account_id();
+        assert!(self.is_owner(&caller_id) || caller_id == self.treasury_id, "{}", ERR024_NOT_ALLOW_RECOVER);
+        caller_id
+    }
+
+    /// Project token inventory not needed by remaining proxy tokens, conversions in flight already took theirs.
+    /// Project token deployed by escrow is held for vesting and conversion, so nothing is recoverable.
+    pub(crate) fn internal_recoverable_inventory_amount(&self) -> Balance {
+        if !self.external_project {
+            return 0;
+        }
+        let remain_proxys = self.circulating_supply.checked_sub(self.converted_amount).unwrap();
+        self.internal_inventory_amount().saturating_sub(remain_proxys.saturating_sub(self.pending_convert_amount))
+    }
+
+    /// Stable coin above expected balance, transfers in flight may not be applied to the queried balance yet
+    /// and deposits of buys in flight are not counted by expected balance until `on_buy`
+    pub(crate) fn internal_recoverable_stable_coin_amount(&self, balance: Balance) -> Balance {
+        let pending_transfer_amount = self.pending_fund_claim
+            + self.pending_finder_fee_claim
+            + self.pending_sell_refund
+            + self.pending_payout_transfer
+            + self.pending_buy_deposit;
+        balance
+            .saturating_sub(self.internal_expected_stable_coin_balance())
+            .saturating_sub(pending_transfer_amount)
+    }
+}
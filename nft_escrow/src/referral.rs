@@ -0,0 +1,91 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Set part of treasury fee credited to referrer of buy, caller should be treasury operator
+    pub fn set_referral_fee(&mut self, fee: u32) {
+        self.assert_role(Role::TreasuryOperator);
+        assert!(fee <= self.treasury_fee, "{}", ERR034_INVALID_REFERRAL_FEE);
+        self.referral_fee = fee;
+
+        log!("Referral fee {}", self.referral_fee);
+    }
+
+    pub fn get_referral_fee(&self) -> u32 { self.referral_fee }
+
+    /// Claim all referral rewards of caller.
+    /// Attached deposit registers caller on stable coin, attach it only when caller is not registered.
+    #[payable]
+    pub fn claim_referral_rewards(&mut self) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let mut referral = self.referral_accounts.get(&account_id).unwrap_or_default();
+        let amount = referral.total_reward - referral.claimed_reward;
+        assert!(amount > 0, "{}", ERR035_NO_REFERRAL_REWARD);
+
+        referral.claimed_reward = referral.total_reward;
+        self.referral_accounts.insert(&account_id, &referral);
+        self.unclaimed_referral_reward = self.unclaimed_referral_reward.checked_sub(amount).unwrap();
+        self.pending_payout_transfer = self.pending_payout_transfer.checked_add(amount).unwrap();
+
+        self.internal_stable_coin_transfer(account_id.clone(), amount, env::attached_deposit())
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .on_claim_referral_rewards(account_id, U128(amount))
+            )
+    }
+
+    #[private]
+    pub fn on_claim_referral_rewards(&mut self, account_id: AccountId, amount: U128) -> bool {
+        self.pending_payout_transfer = self.pending_payout_transfer.checked_sub(amount.0).unwrap();
+        if !is_promise_success() {
+            let mut referral = self.referral_accounts.get(&account_id).unwrap();
+            referral.claimed_reward = referral.claimed_reward.checked_sub(amount.0).unwrap();
+            self.referral_accounts.insert(&account_id, &referral);
+            self.unclaimed_referral_reward = self.unclaimed_referral_reward.checked_add(amount.0).unwrap();
+            return false;
+        }
+
+        log!("Claim referral rewards {} {}", account_id, amount.0);
+        true
+    }
+
+    /// Referred buys and rewards of referrer
+    pub fn get_referral_stats(&self, account_id: AccountId) -> ReferralStats {
+        let referral = self.referral_accounts.get(&account_id).unwrap_or_default();
+        ReferralStats {
+            account_id,
+            referred_buys: referral.referred_buys,
+            referred_amount: U128(referral.referred_amount),
+            total_reward: U128(referral.total_reward),
+            claimed_reward: U128(referral.claimed_reward),
+            claimable_reward: U128(referral.total_reward - referral.claimed_reward),
+        }
+    }
+
+    pub fn get_unclaimed_referral_reward(&self) -> U128 { U128(self.unclaimed_referral_reward) }
+
+    /// Credit referrer for buy paying `amount`, reward never exceeds treasury fee of the buy.
+    /// Buys without reward are not recorded, so zero fee does not grow the ledger.
+    pub(crate) fn internal_credit_referral(&mut self, referrer_id: &AccountId, amount: Balance, treasury_fee_amount: Balance) -> Balance {
+        let reward = amount
+            .checked_mul(self.referral_fee as u128)
+            .unwrap()
+            .checked_div(FEE_DIVISOR as u128)
+            .unwrap()
+            .min(treasury_fee_amount);
+        if reward == 0 {
+            return 0;
+        }
+
+        let mut referral = self.referral_accounts.get(referrer_id).unwrap_or_default();
+        referral.referred_buys += 1;
+        referral.referred_amount = referral.referred_amount.checked_add(amount).unwrap();
+        referral.total_reward = referral.total_reward.checked_add(reward).unwrap();
+        self.referral_accounts.insert(referrer_id, &referral);
+        self.unclaimed_referral_reward = self.unclaimed_referral_reward.checked_add(reward).unwrap();
+
+        log!("Referral {} {} {}", referrer_id, amount, reward);
+        reward
+    }
+}
@@ -0,0 +1,35 @@
+use near_contract_tools::rbac::Rbac;
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Grant role to account, caller should be owner
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        Self::require_owner();
+        self.add_role(&account_id, &role);
+
+        log!("Role {:?} granted to {}", role, account_id);
+    }
+
+    /// Revoke role from account, caller should be owner
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        Self::require_owner();
+        self.remove_role(&account_id, &role);
+
+        log!("Role {:?} revoked from {}", role, account_id);
+    }
+
+    pub fn is_role_granted(&self, account_id: AccountId, role: Role) -> bool {
+        <Self as Rbac<Role>>::has_role(&account_id, &role)
+    }
+
+    /// Owner is allowed to act as any role
+    pub(crate) fn assert_role(&self, role: Role) {
+        let predecessor_id = env::predecessor_account_id();
+        assert!(
+            self.is_owner(&predecessor_id) || <Self as Rbac<Role>>::has_role(&predecessor_id, &role),
+            "{}",
+            ERR23_NOT_ALLOW_ROLE
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use near_contract_standards::storage_management::{StorageBalance, StorageBalanceBounds, StorageManagement};
+use near_sdk::assert_one_yocto;
+use crate::*;
+
+/// History, up to three vote checkpoints, halt vote and referral rewards of account, kept after unregistering
+const STORAGE_ACCOUNT_RECORDS_COST: Balance = 700 * STORAGE_PRICE_PER_BYTE;
+/// Proxy token index and records of account are charged on registration, index is released on unregistering
+const STORAGE_ACCOUNT_COST: Balance = DEPOSIT_PT_OWNER + STORAGE_ACCOUNT_RECORDS_COST;
+
+#[near_bindgen]
+impl StorageManagement for Contract {
+    /// Deposit NEAR paying proxy token storage of account, excess of registration cost is refunded with `registration_only`
+    #[payable]
+    fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+
+        let storage = match self.storage_accounts.get(&account_id) {
+            Some(mut storage) => {
+                if registration_only.unwrap_or(false) {
+                    if amount > 0 {
+                        Promise::new(env::predecessor_account_id()).transfer(amount);
+                    }
+                } else {
+                    storage.total = storage.total.checked_add(amount).unwrap();
+                }
+                storage
+            }
+            None => {
+                assert!(amount >= STORAGE_ACCOUNT_COST, "{}", ERR027_INSUFFICIENT_STORAGE);
+                let total = if registration_only.unwrap_or(false) {
+                    let refund = amount - STORAGE_ACCOUNT_COST;
+                    if refund > 0 {
+                        Promise::new(env::predecessor_account_id()).transfer(refund);
+                    }
+                    STORAGE_ACCOUNT_COST
+                } else {
+                    amount
+                };
+                AccountStorage { total, used: STORAGE_ACCOUNT_COST }
+            }
+        };
+        self.storage_accounts.insert(&account_id, &storage);
+
+        storage.into()
+    }
+
+    /// Withdraw NEAR not used by proxy tokens, all available if `amount` is not given
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut storage = self.internal_unwrap_account_storage(&account_id);
+
+        let available = storage.total - storage.used;
+        let amount = amount.map_or(available, |amount| amount.0);
+        assert!(amount <= available, "{}", ERR027_INSUFFICIENT_STORAGE);
+
+        storage.total -= amount;
+        self.storage_accounts.insert(&account_id, &storage);
+        if amount > 0 {
+            Promise::new(account_id).transfer(amount);
+        }
+
+        storage.into()
+    }
+
+    /// Unregister account holding no proxy tokens and refund deposit, `force` is not supported.
+    /// Cost of records is not refunded once account has any, since they are kept.
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        assert!(!force.unwrap_or(false), "{}", ERR028_STORAGE_IN_USE);
+        let account_id = env::predecessor_account_id();
+
+        match self.storage_accounts.get(&account_id) {
+            Some(storage) => {
+                assert!(
+                    storage.used == STORAGE_ACCOUNT_COST && self.pt_tokens_per_owner.get(&account_id).is_none(),
+                    "{}",
+                    ERR028_STORAGE_IN_USE
+                );
+                self.storage_accounts.remove(&account_id);
+                let has_records = self.account_histories.get(&account_id).is_some()
+                    || self.vote_checkpoints.get(&account_id).is_some()
+                    || self.halt_votes.get(&account_id).is_some()
+                    || self.referral_accounts.get(&account_id).is_some();
+                let refund = if has_records { storage.total - STORAGE_ACCOUNT_RECORDS_COST } else { storage.total };
+                Promise::new(account_id).transfer(refund);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(STORAGE_ACCOUNT_COST),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_accounts.get(&account_id).map(|storage| storage.into())
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub(crate) fn internal_unwrap_account_storage(&self, account_id: &AccountId) -> AccountStorage {
+        self.storage_accounts.get(account_id).unwrap_or_else(|| env::panic_str(ERR026_NOT_REGISTERED))
+    }
+
+    /// Charge storage of proxy tokens to be minted to account
+    pub(crate) fn internal_charge_storage(&mut self, account_id: &AccountId, amount: u128) {
+        let mut storage = self.internal_unwrap_account_storage(account_id);
+        let cost = amount.checked_mul(DEPOSIT_ONE_PT_MINT).unwrap();
+        assert!(storage.total - storage.used >= cost, "{}", ERR027_INSUFFICIENT_STORAGE);
+
+        storage.used += cost;
+        self.storage_accounts.insert(account_id, &storage);
+    }
+
+    /// Release storage of burned proxy tokens, registration cost is kept until unregistering
+    pub(crate) fn internal_release_storage(&mut self, account_id: &AccountId, amount: u128) {
+        if let Some(mut storage) = self.storage_accounts.get(account_id) {
+            let cost = amount.checked_mul(DEPOSIT_ONE_PT_MINT).unwrap();
+            storage.used = storage.used.saturating_sub(cost).max(STORAGE_ACCOUNT_COST);
+            self.storage_accounts.insert(account_id, &storage);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenReceiver;
 use near_sdk::{AccountId, env, PromiseOrValue};
 use near_sdk::json_types::U128;
 
@@ -6,18 +7,51 @@ use crate::*;
 
 #[near_bindgen]
 impl FungibleTokenReceiver for Contract {
-    #[allow(unreachable_code)]
+    /// Buy proxy token with stable coin, unknown messages return the whole amount
     fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
-        self.assert_not_paused();
-        self.assert_is_ongoing();
-
         let token_in = env::predecessor_account_id();
+
+        // conversion inventory of external project
+        if self.external_project
+            && self.project_token_type == ProjectTokenType::Fungible
+            && Some(&token_in) == self.project_token_id.as_ref() {
+            self.inventory_amount = self.inventory_amount.checked_add(amount.0).unwrap();
+            log!("Inventory {} {}", sender_id, amount.0);
+            return PromiseOrValue::Value(U128(0));
+        }
+
         let args = msg.split(":").collect::<Vec<&str>>();
 
-        if args.len() == 2 && args[0] == "buy" && token_in == self.stable_coin_id {
-            self.buy(sender_id, U128(args[1].parse::<u128>().unwrap()), amount);
+        // `buy:<amount>` or `buy:<amount>:<referrer-id>`
+        if (args.len() == 2 || args.len() == 3) && args[0] == "buy" && token_in == self.stable_coin_id {
+            self.assert_not_paused();
+            self.assert_is_ongoing();
+
+            let referrer_id = args.get(2).map(|referrer_id| {
+                referrer_id.parse::<AccountId>().unwrap_or_else(|_| env::panic_str(ERR033_INVALID_REFERRER))
+            });
+            self.buy(sender_id, U128(args[1].parse::<u128>().unwrap()), amount, referrer_id);
+            return PromiseOrValue::Value(U128(0));
+        }
+
+        log!("Return {} {} {}", token_in, sender_id, amount.0);
+        PromiseOrValue::Value(amount)
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenReceiver for Contract {
+    /// Receive conversion inventory of external project, other tokens are returned
+    fn nft_on_transfer(&mut self, sender_id: AccountId, previous_owner_id: AccountId, token_id: TokenId, msg: String) -> PromiseOrValue<bool> {
+        let _ = msg;
+        if self.external_project
+            && self.project_token_type == ProjectTokenType::NonFungible
+            && Some(&env::predecessor_account_id()) == self.project_token_id.as_ref() {
+            self.inventory_token_ids.push(&token_id);
+            log!("Inventory {} {} {}", sender_id, previous_owner_id, token_id);
+            return PromiseOrValue::Value(false);
         }
 
-        PromiseOrValue::Value(U128(0))
+        PromiseOrValue::Value(true)
     }
-}
\ No newline at end of file
+}
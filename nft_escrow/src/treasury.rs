@@ -0,0 +1,90 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Claim accrued treasury fee, all accrued fee if `amount` is not given. Caller should be treasury.
+    /// Attached deposit registers treasury on stable coin, attach it only when treasury is not registered.
+    #[payable]
+    pub fn claim_treasury_fee(&mut self, amount: Option<U128>) -> Promise {
+        let account_id = env::predecessor_account_id();
+        assert!(account_id == self.treasury_id, "{}", ERR030_NOT_ALLOW_TREASURY);
+
+        let amount = amount.map_or(self.accrued_treasury_fee, |amount| amount.0);
+        assert!(amount > 0, "{}", ERR010_INVALID_AMOUNT);
+        assert!(amount <= self.accrued_treasury_fee, "{}", ERR031_INSUFFICIENT_TREASURY_FEE);
+        self.accrued_treasury_fee -= amount;
+        self.pending_payout_transfer = self.pending_payout_transfer.checked_add(amount).unwrap();
+
+        self.internal_stable_coin_transfer(account_id.clone(), amount, env::attached_deposit())
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .on_claim_treasury_fee(account_id, U128(amount))
+            )
+    }
+
+    #[private]
+    pub fn on_claim_treasury_fee(&mut self, account_id: AccountId, amount: U128) -> bool {
+        self.pending_payout_transfer = self.pending_payout_transfer.checked_sub(amount.0).unwrap();
+        if !is_promise_success() {
+            self.accrued_treasury_fee = self.accrued_treasury_fee.checked_add(amount.0).unwrap();
+            return false;
+        }
+
+        log!("Claim treasury fee {} {}", account_id, amount.0);
+        true
+    }
+
+    /// Enable or disable treasury fee accrual, caller should be treasury operator.
+    /// Fee accrued before disabling stays claimable.
+    pub fn set_treasury_fee_accrual(&mut self, enabled: bool) {
+        self.assert_role(Role::TreasuryOperator);
+        self.treasury_fee_accrual = enabled;
+
+        log!("Treasury fee accrual {}", enabled);
+    }
+
+    pub fn is_treasury_fee_accrual(&self) -> bool { self.treasury_fee_accrual }
+
+    /// Set fee withheld from sell refunds and where it goes, caller should be treasury operator
+    pub fn set_sell_fee(&mut self, fee: u32, destination: SellFeeDestination) {
+        self.assert_role(Role::TreasuryOperator);
+        assert!(fee <= MAX_SELL_FEE, "{}", ERR032_INVALID_SELL_FEE);
+        self.sell_fee = fee;
+        self.sell_fee_destination = destination;
+
+        log!("Sell fee {} {:?}", self.sell_fee, self.sell_fee_destination);
+    }
+
+    pub fn get_sell_fee(&self) -> u32 { self.sell_fee }
+
+    pub fn get_sell_fee_destination(&self) -> SellFeeDestination { self.sell_fee_destination.clone() }
+
+    /// Treasury fee waiting for `claim_treasury_fee`
+    pub fn get_accrued_treasury_fee(&self) -> U128 { U128(self.accrued_treasury_fee) }
+
+    /// Move sell fee out of reserve unless it stays there, fee of projects without finder stays in reserve
+    pub(crate) fn internal_distribute_sell_fee(&mut self, fee: Balance, destination: SellFeeDestination) {
+        if fee == 0 {
+            return;
+        }
+
+        let receiver_id = match destination {
+            SellFeeDestination::Reserve => return,
+            SellFeeDestination::Treasury => self.treasury_id.clone(),
+            SellFeeDestination::Finder => match self.finder_id.clone() {
+                Some(finder_id) => finder_id,
+                None => return,
+            },
+        };
+        self.total_fund_amount = self.total_fund_amount.checked_sub(fee).unwrap();
+
+        if destination == SellFeeDestination::Treasury && self.treasury_fee_accrual {
+            self.accrued_treasury_fee = self.accrued_treasury_fee.checked_add(fee).unwrap();
+        } else {
+            self.internal_payout_transfer(receiver_id, fee);
+        }
+
+        log!("Sell fee {:?} {}", destination, fee);
+    }
+}
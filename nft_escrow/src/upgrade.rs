@@ -0,0 +1,270 @@
+use near_sdk::json_types::Base64VecU8;
+use crate::*;
+
+const GAS_FOR_MIGRATE: Gas = Gas(50 * TGAS);
+const GAS_FOR_PROJECT_TOKEN_UPGRADE: Gas = Gas(100 * TGAS);
+const STATE_VERSION_KEY: &[u8] = b"~v";
+
+/// Layout version of escrow state stored under `STATE_VERSION_KEY`.
+/// When `Contract` layout changes, freeze the current layout as a legacy struct,
+/// add a new version and migrate it in `migrate`.
+#[derive(BorshDeserialize, BorshSerialize, PartialEq, Clone, Debug)]
+pub enum StateVersion {
+    /// Unversioned layout before upgrade support
+    V0,
+    V1,
+}
+
+pub(crate) const CURRENT_STATE_VERSION: StateVersion = StateVersion::V1;
+
+/// Escrow state layout of `StateVersion::V0`
+#[derive(BorshDeserialize)]
+pub struct ContractV0 {
+    owner_id: AccountId,
+    name: String,
+    symbol: String,
+    treasury_id: AccountId,
+    treasury_fee: u32,
+    finder_id: Option<AccountId>,
+    finder_fee: u32,
+    project_token_type: ProjectTokenType,
+    project_token_id: Option<AccountId>,
+    fund_threshold: Balance,
+    start_timestamp: u64,
+    tp_timestamp: u64,
+    buffer_period: u64,
+    conversion_period: u64,
+    stable_coin_id: AccountId,
+    stable_coin_decimals: u8,
+    total_fund_amount: Balance,
+    claimed_fund_amount: Balance,
+    claimed_finder_fee: Balance,
+    pre_mint_amount: Balance,
+    converted_amount: Balance,
+    circulating_supply: Balance,
+    curve_type: CurveType,
+    curve_args: CurveArgs,
+    state: RunningState,
+    closed_step: ClosedStep,
+    pt_media_uri: String,
+    pt_max_supply: u128,
+    pt_all_total_supply: Balance,
+    pt_total_supply: LookupMap<TokenId, Balance>,
+    pt_balances_per_token: UnorderedMap<TokenId, LookupMap<AccountId, Balance>>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Deploy new code and migrate state, caller should be owner
+    pub fn upgrade(&mut self, code: Base64VecU8) -> Promise {
+        Self::require_owner();
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code.into())
+            .function_call(
+                "migrate".to_string(),
+                vec![],
+                NO_DEPOSIT,
+                GAS_FOR_MIGRATE
+            )
+    }
+
+    /// Push new code to project token and migrate its state, caller should be owner.
+    /// Escrow should still own project token, i.e. before the owner accepts ownership on closing.
+    pub fn upgrade_project_token(&mut self, code: Base64VecU8) -> Promise {
+        Self::require_owner();
+        assert!(!self.external_project, "{}", ERR017_NOT_ALLOW_EXTERNAL);
+        let project_token_id = self.project_token_id.clone().unwrap_or_else(|| env::panic_str(ERR10_NOT_ACTIVATED));
+
+        log!("Upgrade project token {}", project_token_id);
+        match self.project_token_type {
+            ProjectTokenType::NonFungible => ext_nft_collection::ext(project_token_id)
+                .with_static_gas(GAS_FOR_PROJECT_TOKEN_UPGRADE)
+                .upgrade(code),
+            ProjectTokenType::Fungible => ext_fungible_token::ext(project_token_id)
+                .with_static_gas(GAS_FOR_PROJECT_TOKEN_UPGRADE)
+                .upgrade(code),
+        }
+    }
+
+    /// Migrate state of previous layout, called by `upgrade` after deploying new code.
+    /// Tokens per owner and voting weights of `V0` holders are indexed by `migrate_holders`,
+    /// histories introduced after `V0` start empty.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let version = env::storage_read(STATE_VERSION_KEY)
+            .map(|bytes| StateVersion::try_from_slice(&bytes).unwrap())
+            .unwrap_or(StateVersion::V0);
+
+        let contract = match version {
+            StateVersion::V0 => {
+                let old: ContractV0 = env::state_read().unwrap_or_else(|| env::panic_str(ERR80_NO_STATE));
+                let mut contract = Self {
+                    name: old.name,
+                    symbol: old.symbol,
+                    treasury_id: old.treasury_id,
+                    treasury_fee: old.treasury_fee,
+                    finder_id: old.finder_id,
+                    finder_fee: old.finder_fee,
+                    project_token_type: old.project_token_type,
+                    project_token_id: old.project_token_id,
+                    pending_activation: None,
+                    external_project: false,
+                    inventory_amount: 0,
+                    inventory_token_ids: Vector::new(StorageKey::InventoryTokenIds),
+                    fund_threshold: old.fund_threshold,
+                    fund_hard_cap: None,
+                    sale_end_timestamp: None,
+                    start_timestamp: old.start_timestamp,
+                    tp_timestamp: old.tp_timestamp,
+                    buffer_period: old.buffer_period,
+                    conversion_period: old.conversion_period,
+                    stable_coin_id: old.stable_coin_id,
+                    stable_coin_decimals: old.stable_coin_decimals,
+                    total_fund_amount: old.total_fund_amount,
+                    claimed_fund_amount: old.claimed_fund_amount,
+                    claimed_finder_fee: old.claimed_finder_fee,
+                    pending_fund_claim: 0,
+                    pending_finder_fee_claim: 0,
+                    pending_buy_fund: 0,
+                    pending_buy_deposit: 0,
+                    pending_payout_transfer: 0,
+                    pending_sell_refund: 0,
+                    pending_sell_amount: 0,
+                    pending_convert_amount: 0,
+                    pending_accounts: LookupSet::new(StorageKey::PendingAccounts),
+                    pending_payouts: UnorderedMap::new(StorageKey::PendingPayouts),
+                    total_pending_payout: 0,
+                    accrued_treasury_fee: 0,
+                    // escrows deployed before accrual keep transferring fee on every buy
+                    treasury_fee_accrual: false,
+                    sell_fee: 0,
+                    sell_fee_destination: SellFeeDestination::Reserve,
+                    referral_fee: 0,
+                    referral_accounts: LookupMap::new(StorageKey::ReferralAccounts),
+                    unclaimed_referral_reward: 0,
+                    fund_release_mode: FundReleaseMode::Owner,
+                    fund_release_proposal_period: 0,
+                    next_fund_release_id: 0,
+                    fund_release_proposals: LookupMap::new(StorageKey::FundReleaseProposals),
+                    approved_fund_release_amount: 0,
+                    milestones: vec![],
+                    milestone_verifier_id: None,
+                    halt_config: HaltConfig {
+                        quorum: 2000,       // 20%
+                        threshold: 5000,    // 50%
+                        voting_period: 7 * 86400 * 1_000_000_000,
+                    },
+                    next_halt_proposal_id: 0,
+                    active_halt_proposal_id: None,
+                    halt_proposals: LookupMap::new(StorageKey::HaltProposals),
+                    halt_votes: LookupMap::new(StorageKey::HaltVotes),
+                    vote_checkpoints: LookupMap::new(StorageKey::VoteCheckpoints),
+                    halted_timestamp: None,
+                    // converters before `V0` migration are unknown, so their amount is not weighted
+                    unweighted_converted_amount: old.converted_amount,
+                    pre_mint_amount: old.pre_mint_amount,
+                    pre_mint_vesting: None,
+                    pre_mint_vesting_start: None,
+                    pre_mint_token_offset: 0,
+                    pre_mint_claimed_amount: 0,
+                    allocations: vec![],
+                    allocation_token_offset: None,
+                    remain_proxy_token_offset: old.pre_mint_amount,
+                    converted_amount: old.converted_amount,
+                    circulating_supply: old.circulating_supply,
+                    curve_type: old.curve_type,
+                    curve_args: old.curve_args,
+                    state: old.state,
+                    closed_step: old.closed_step,
+                    pt_media_uri: old.pt_media_uri,
+                    pt_total_supply: old.pt_total_supply,
+                    pt_balances_per_token: old.pt_balances_per_token,
+                    pt_tokens_per_owner: LookupMap::new(StorageKey::TokensPerOwner),
+                    unindexed_proxy_amount: old.pt_all_total_supply,
+                    account_histories: LookupMap::new(StorageKey::AccountHistories),
+                    storage_accounts: LookupMap::new(StorageKey::StorageAccounts),
+                    pt_max_supply: old.pt_max_supply,
+                    pt_all_total_supply: old.pt_all_total_supply,
+                };
+                // owner moves from escrow state to `Owner` component
+                Owner::init(&mut contract, &old.owner_id);
+                contract
+            }
+            StateVersion::V1 => env::state_read().unwrap_or_else(|| env::panic_str(ERR80_NO_STATE)),
+        };
+
+        Self::internal_write_state_version();
+
+        log!("Migrate {:?} -> {:?}", version, CURRENT_STATE_VERSION);
+        contract
+    }
+
+    /// Index proxy tokens of `V0` holders into tokens per owner and voting weights, caller should be owner.
+    /// Token range `from_index..from_index + limit` of `pt_balances_per_token` is checked for each account,
+    /// attached deposit covers storage of indexes. Holder operations are blocked until all are indexed
+    /// or `finish_holders_migration`, holders missed by then are still indexed later.
+    #[payable]
+    pub fn migrate_holders(&mut self, account_ids: Vec<AccountId>, from_index: u64, limit: u64) {
+        Self::require_owner();
+
+        let initial_storage_usage = env::storage_usage();
+        let to_index = std::cmp::min(from_index.saturating_add(limit), self.pt_balances_per_token.len());
+        let tokens: Vec<(TokenId, LookupMap<AccountId, Balance>)> = (from_index..to_index)
+            .map(|index| (
+                self.pt_balances_per_token.keys_as_vector().get(index).unwrap(),
+                self.pt_balances_per_token.values_as_vector().get(index).unwrap(),
+            ))
+            .collect();
+
+        for account_id in account_ids {
+            let mut indexed_amount = 0;
+            let mut weight = 0;
+            for (token_id, balances) in tokens.iter() {
+                let balance = balances.get(&account_id).unwrap_or(0);
+                let indexed = self.pt_tokens_per_owner.get(&account_id).is_some_and(|ids| ids.contains(token_id));
+                if balance == 0 || indexed {
+                    continue;
+                }
+                self.internal_add_pt_to_owner(&account_id, token_id);
+                indexed_amount += balance;
+                if !self.internal_is_pre_mint_token(&account_id, token_id) {
+                    weight += balance;
+                }
+            }
+            if indexed_amount == 0 {
+                continue;
+            }
+            self.unindexed_proxy_amount = self.unindexed_proxy_amount.saturating_sub(indexed_amount);
+            if weight > 0 {
+                let vote_weight = self.internal_vote_weight(&account_id, None);
+                self.internal_checkpoint_vote_weight(&account_id, vote_weight.checked_add(weight).unwrap());
+            }
+        }
+
+        refund_deposit_to_account(env::storage_usage() - initial_storage_usage, env::predecessor_account_id());
+
+        log!("Migrate holders {}..{}, unindexed {}", from_index, to_index, self.unindexed_proxy_amount);
+    }
+
+    /// Unblock holder operations although some `V0` proxy tokens are not indexed, caller should be owner.
+    /// Holders missed by `migrate_holders` have no voting weight until they are indexed.
+    pub fn finish_holders_migration(&mut self) {
+        Self::require_owner();
+        assert!(self.unindexed_proxy_amount > 0, "{}", ERR81_HOLDERS_MIGRATED);
+
+        log!("Finish holders migration, unindexed {}", self.unindexed_proxy_amount);
+        self.unindexed_proxy_amount = 0;
+    }
+
+    pub fn get_state_version(&self) -> u8 {
+        env::storage_read(STATE_VERSION_KEY)
+            .map(|bytes| StateVersion::try_from_slice(&bytes).unwrap())
+            .unwrap_or(StateVersion::V0) as u8
+    }
+
+    pub(crate) fn internal_write_state_version() {
+        env::storage_write(STATE_VERSION_KEY, &CURRENT_STATE_VERSION.try_to_vec().unwrap());
+    }
+}
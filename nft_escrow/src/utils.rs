@@ -1,9 +1,9 @@
 use near_contract_standards::non_fungible_token::TokenId;
-use near_sdk::{ext_contract, AccountId, Gas, Balance, PromiseOrValue, env, require, Promise};
-use near_sdk::json_types::{U128};
+use near_contract_standards::storage_management::StorageBalance;
+use near_sdk::{ext_contract, AccountId, Gas, Balance, BorshStorageKey, PromiseOrValue, env, require, Promise};
+use near_sdk::json_types::{Base64VecU8, U128};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{Deserialize, Serialize};
-use uint::construct_uint;
 
 
 /// Fee divisor, allowing to provide fee in bps.
@@ -18,6 +18,27 @@ pub enum RunningState {
     Paused,
 }
 
+#[derive(BorshSerialize, BorshStorageKey, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    Admin,
+    Pauser,
+    FundClaimer,
+    CurveManager,
+    TreasuryOperator,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Phase {
+    NotActivated,
+    Funding,
+    Buffer,
+    Conversion,
+    Closed,
+    Refunding,
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub enum ProjectTokenType {
@@ -80,6 +101,347 @@ pub struct CurveArgs {
     pub arg_d: Option<u128>,
 }
 
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SellFeeDestination {
+    /// Fee stays in reserve, backing proxy tokens of remaining holders
+    Reserve,
+    /// Fee leaves reserve as treasury fee
+    Treasury,
+    /// Fee leaves reserve and is transferred to finder
+    Finder,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum FundReleaseMode {
+    /// Fund is claimed by owner or fund claimer directly
+    Owner,
+    /// Fund is released after `threshold` of `signers` approved
+    Multisig { signers: Vec<AccountId>, threshold: u32 },
+    /// Fund is released after DAO approved by function call
+    Dao { dao_id: AccountId },
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalStatus {
+    Pending,
+    Approved,
+    Executed,
+    Cancelled,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundReleaseProposal {
+    pub id: u64,
+    pub proposer_id: AccountId,
+    pub to: AccountId,
+    pub amount: U128,
+    pub approvals: Vec<AccountId>,
+    pub status: ProposalStatus,
+    pub expires_at: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum MilestoneStatus {
+    Pending,
+    Submitted,
+    Approved,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MilestoneArgs {
+    pub title: String,
+    /// Percent of raised fund in bps
+    pub percent: u32,
+    pub unlock_timestamp: Option<u64>,
+    pub requires_approval: bool,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Milestone {
+    pub title: String,
+    /// Percent of raised fund in bps
+    pub percent: u32,
+    pub unlock_timestamp: Option<u64>,
+    pub requires_approval: bool,
+    pub status: MilestoneStatus,
+    /// Submitted proof of work, e.g. uri
+    pub evidence: Option<String>,
+}
+
+impl Milestone {
+    pub fn is_unlocked(&self, timestamp: u64) -> bool {
+        self.unlock_timestamp.map_or(true, |unlock_timestamp| timestamp >= unlock_timestamp)
+            && (!self.requires_approval || self.status == MilestoneStatus::Approved)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundLockInfo {
+    /// Raised fund excluding finder fee
+    pub total_fund: U128,
+    pub unlocked_fund: U128,
+    pub locked_fund: U128,
+    pub claimed_fund: U128,
+}
+
+/// Stable coin of failed transfers kept for account
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingPayout {
+    pub account_id: AccountId,
+    pub amount: U128,
+}
+
+/// Storage deposit of account and the part used by its proxy tokens
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct AccountStorage {
+    pub total: Balance,
+    pub used: Balance,
+}
+
+impl From<AccountStorage> for StorageBalance {
+    fn from(storage: AccountStorage) -> Self {
+        Self {
+            total: U128(storage.total),
+            available: U128(storage.total - storage.used),
+        }
+    }
+}
+
+/// Config overwritten by activation, restored when project token deployment fails
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct PendingActivation {
+    pub caller_id: AccountId,
+    pub deposit: Balance,
+    /// Part of deposit transferred to project token account
+    pub storage_cost: Balance,
+    pub finder_id: Option<AccountId>,
+    pub fund_threshold: Balance,
+    pub buffer_period: u64,
+    pub conversion_period: u64,
+    pub project_token_type: ProjectTokenType,
+    pub pt_max_supply: u128,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VestingSchedule {
+    /// Nothing is vested until cliff passes
+    pub cliff: u64,
+    /// Linear vesting duration from start, including cliff
+    pub duration: u64,
+}
+
+impl VestingSchedule {
+    pub fn vested_amount(&self, total: Balance, start_timestamp: u64, timestamp: u64) -> Balance {
+        let elapsed = timestamp.saturating_sub(start_timestamp);
+        if elapsed < self.cliff {
+            0
+        } else if elapsed >= self.duration {
+            total
+        } else {
+            total.checked_mul(elapsed as u128).unwrap().checked_div(self.duration as u128).unwrap()
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Allocation {
+    /// Allocation name, e.g. team, advisors, marketing
+    pub name: String,
+    pub beneficiary_id: AccountId,
+    pub amount: U128,
+    /// Vesting starts when funding threshold is reached
+    pub schedule: VestingSchedule,
+    pub claimed_amount: U128,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VestingInfo {
+    pub schedule: Option<VestingSchedule>,
+    pub start_timestamp: Option<u64>,
+    pub total_amount: U128,
+    pub vested_amount: U128,
+    pub claimed_amount: U128,
+    pub claimable_amount: U128,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HaltConfig {
+    /// Minimum participation in bps of total voting weight
+    pub quorum: u32,
+    /// Minimum approval in bps of participated voting weight
+    pub threshold: u32,
+    /// Voting period
+    pub voting_period: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum HaltProposalStatus {
+    Voting,
+    Passed,
+    Rejected,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HaltProposal {
+    pub id: u64,
+    pub proposer_id: AccountId,
+    pub reason: String,
+    /// Voting weight is taken from the end of previous block
+    pub snapshot_height: u64,
+    pub total_weight: U128,
+    pub votes_for: U128,
+    pub votes_against: U128,
+    pub ends_at: u64,
+    pub status: HaltProposalStatus,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+pub struct VoteCheckpoint {
+    pub block_height: u64,
+    pub weight: Balance,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Default, PartialEq, Clone, Debug)]
+pub struct ReferralAccount {
+    /// Number of buys referred by account
+    pub referred_buys: u64,
+    /// Total stable coin paid by referred buys
+    pub referred_amount: Balance,
+    /// Total referral reward credited
+    pub total_reward: Balance,
+    /// Referral reward claimed or being claimed
+    pub claimed_reward: Balance,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReferralStats {
+    pub account_id: AccountId,
+    pub referred_buys: u64,
+    pub referred_amount: U128,
+    pub total_reward: U128,
+    pub claimed_reward: U128,
+    pub claimable_reward: U128,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Default, PartialEq, Clone, Debug)]
+pub struct AccountHistory {
+    /// Total stable coin paid for buying proxy tokens
+    pub paid_amount: Balance,
+    /// Total stable coin refunded by selling proxy tokens
+    pub refunded_amount: Balance,
+    /// Total proxy tokens converted to project tokens
+    pub converted_amount: Balance,
+}
+
+/// Escrow accounting and violated invariants
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InvariantReport {
+    pub is_valid: bool,
+    pub violations: Vec<String>,
+    pub total_fund_amount: U128,
+    pub claimed_fund_amount: U128,
+    pub claimed_finder_fee: U128,
+    pub circulating_supply: U128,
+    pub converted_amount: U128,
+    pub pt_all_total_supply: U128,
+    /// Stable coin escrow should hold for reserve
+    pub expected_stable_coin_balance: U128,
+    /// Actual stable coin balance of escrow, only queried by `reconcile_stable_coin`
+    pub stable_coin_balance: Option<U128>,
+}
+
+/// Amounts reserved by operations waiting for their callbacks
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingOperations {
+    pub buy_fund: U128,
+    pub buy_deposit: U128,
+    pub payout_transfer: U128,
+    pub sell_refund: U128,
+    pub sell_amount: U128,
+    pub convert_amount: U128,
+    pub fund_claim: U128,
+    pub finder_fee_claim: U128,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountPosition {
+    pub account_id: AccountId,
+    pub token_ids: Vec<TokenId>,
+    pub token_count: U128,
+    /// Stable coin amount received by selling all proxy tokens now
+    pub sell_value: U128,
+    pub paid_amount: U128,
+    pub refunded_amount: U128,
+    pub converted_amount: U128,
+    pub can_sell: bool,
+    pub can_convert: bool,
+    pub can_refund: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProjectInfo {
+    pub owner_id: Option<AccountId>,
+    pub name: String,
+    pub symbol: String,
+    pub treasury_id: AccountId,
+    pub treasury_fee: u32,
+    pub finder_id: Option<AccountId>,
+    pub finder_fee: u32,
+    pub project_token_type: ProjectTokenType,
+    pub project_token_id: Option<AccountId>,
+    pub stable_coin_id: AccountId,
+    pub stable_coin_decimals: u8,
+    pub curve_type: CurveType,
+    pub curve_args: CurveArgs,
+    pub pt_media_uri: String,
+    pub fund_threshold: U128,
+    pub fund_hard_cap: Option<U128>,
+    pub state: RunningState,
+    pub closed_step: ClosedStep,
+    pub phase: Phase,
+    pub start_timestamp: u64,
+    pub tp_timestamp: u64,
+    pub buffer_period: u64,
+    pub conversion_period: u64,
+    pub sale_end_timestamp: Option<u64>,
+    pub buffer_end_timestamp: Option<u64>,
+    pub conversion_end_timestamp: Option<u64>,
+    /// Price of the next proxy token
+    pub current_price: U128,
+    pub pt_max_supply: U128,
+    pub pt_all_total_supply: U128,
+    pub pre_mint_amount: U128,
+    pub circulating_supply: U128,
+    pub converted_amount: U128,
+    pub total_fund_amount: U128,
+    pub claimed_fund_amount: U128,
+    pub claimed_finder_fee: U128,
+    /// Fund amount the owner can claim now
+    pub claimable_fund: U128,
+    /// Finder fee amount the finder can claim now
+    pub claimable_finder_fee: U128,
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct FungibleTokenArgs {
@@ -120,12 +482,24 @@ pub trait SelfCallbacks {
         &mut self,
         project_token_id: AccountId
     ) -> PromiseOrValue<bool>;
-    fn on_buy(&mut self, from: AccountId, amount: U128, deposit: U128, reserve: U128) -> bool;
-    fn on_sell(&mut self, from: AccountId, refund: U128, token_ids: Vec<TokenId>) -> bool;
-    fn on_convert(&mut self, from: AccountId, token_ids: Vec<TokenId>) -> bool;
-    fn on_claim_fund(&mut self, amount: U128);
+    fn on_buy(&mut self, from: AccountId, amount: U128, deposit: U128, reserve: U128, referrer_id: Option<AccountId>) -> bool;
+    fn on_sell(&mut self, from: AccountId, refund: U128, fee: U128, fee_destination: SellFeeDestination, token_ids: Vec<TokenId>) -> bool;
+    fn on_convert(&mut self, from: AccountId, token_ids: Vec<TokenId>, inventory_token_ids: Option<Vec<TokenId>>) -> bool;
+    fn on_claim_fund(&mut self, to: AccountId, amount: U128, proposal_id: Option<u64>);
     fn on_claim_finder_fee(&mut self, amount: U128);
-    fn on_close_project(&mut self);
+    fn on_claim_halt_refund(&mut self, account_id: AccountId, weight: U128, amount: U128, token_ids: Vec<TokenId>) -> bool;
+    fn on_claim_vested(&mut self, amount: U128) -> bool;
+    fn on_claim_allocation(&mut self, index: u32, amount: U128) -> bool;
+    fn on_close_project(&mut self, inventory_token_ids: Option<Vec<TokenId>>);
+    fn on_reconcile_stable_coin(&self) -> InvariantReport;
+    fn on_recover_stable_coin(&mut self, receiver_id: AccountId, amount: U128) -> Promise;
+    fn on_recover_inventory(&mut self, amount: U128, token_ids: Vec<TokenId>) -> bool;
+    fn on_withdraw_pending(&mut self, account_id: AccountId, amount: U128) -> bool;
+    fn on_payout_transfer(&mut self, account_id: AccountId, amount: U128) -> bool;
+    fn on_ft_storage_balance_of(&mut self, token_id: AccountId, account_id: AccountId, deposit: U128, refund_id: AccountId) -> PromiseOrValue<bool>;
+    fn on_ft_storage_deposit(&mut self, deposit: U128, refund_id: AccountId) -> bool;
+    fn on_claim_treasury_fee(&mut self, account_id: AccountId, amount: U128) -> bool;
+    fn on_claim_referral_rewards(&mut self, account_id: AccountId, amount: U128) -> bool;
     fn pt_mint(&mut self, receiver_id: AccountId, amount: U128);
 }
 
@@ -135,8 +509,9 @@ pub trait NonFungibleToken {
     fn nft_mint(&mut self, receiver_id: AccountId, amount: U128);
     fn nft_transfer(&mut self, receiver_id: AccountId, token_id: TokenId, approval_id: Option<u64>, memo: Option<String>);
     fn nft_batch_transfer(&mut self, to: AccountId, token_ids: Vec<TokenId>, memo: Option<String>);
-    fn get_owner(&self) -> AccountId;
-    fn set_owner(&mut self, owner_id: AccountId);
+    fn own_get_owner(&self) -> Option<AccountId>;
+    fn own_propose_owner(&mut self, account_id: Option<AccountId>);
+    fn upgrade(&mut self, code: Base64VecU8);
 }
 
 #[ext_contract(ext_fungible_token)]
@@ -144,14 +519,24 @@ pub trait FungibleToken {
     fn new(&mut self, name: String, symbol: String);
     fn ft_mint(&mut self, receiver_id: AccountId, amount: U128);
     fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
-    fn get_owner(&self) -> AccountId;
-    fn set_owner(&mut self, owner_id: AccountId);
+    fn ft_balance_of(&self, account_id: AccountId) -> U128;
+    fn storage_deposit(&mut self, account_id: Option<AccountId>, registration_only: Option<bool>) -> StorageBalance;
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance>;
+    fn own_get_owner(&self) -> Option<AccountId>;
+    fn own_propose_owner(&mut self, account_id: Option<AccountId>);
+    fn upgrade(&mut self, code: Base64VecU8);
 }
 
-construct_uint! {
-    /// 256-bit unsigned integer.
-    pub struct U256(4);
+#[allow(clippy::assign_op_pattern)]
+mod u256 {
+    use uint::construct_uint;
+
+    construct_uint! {
+        /// 256-bit unsigned integer.
+        pub struct U256(4);
+    }
 }
+pub use self::u256::U256;
 
 /// Newton's method of integer square root.
 pub fn integer_sqrt(value: U256) -> U256 {
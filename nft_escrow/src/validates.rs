@@ -41,4 +41,34 @@ impl Contract {
             ERR14_NOT_OVER_CONVERSION_PERIOD
         );
     }
+
+    pub(crate) fn assert_not_halted(&self) {
+        assert!(self.halted_timestamp.is_none(), "{}", ERR18_HALTED);
+    }
+
+    /// Holder indexes are incomplete until `migrate_holders` indexed all proxy tokens of `V0` or owner finished migration
+    pub(crate) fn assert_holders_migrated(&self) {
+        assert_eq!(self.unindexed_proxy_amount, 0, "{}", ERR037_HOLDERS_NOT_MIGRATED);
+    }
+
+    /// Buys waiting for `on_buy` count against hard cap
+    pub(crate) fn assert_is_on_sale(&self, reserve_fund_amount: Balance) {
+        assert!(!self.is_sale_ended(), "{}", ERR16_SALE_ENDED);
+        assert!(
+            self.fund_hard_cap.map_or(true, |hard_cap| {
+                self.total_fund_amount
+                    .checked_add(self.pending_buy_fund)
+                    .unwrap()
+                    .checked_add(reserve_fund_amount)
+                    .unwrap() <= hard_cap
+            }),
+            "{}",
+            ERR17_OVER_HARD_CAP
+        );
+    }
+
+    pub(crate) fn is_sale_ended(&self) -> bool {
+        self.sale_end_timestamp.is_some_and(|end| env::block_timestamp() >= end)
+            || self.fund_hard_cap.is_some_and(|hard_cap| self.total_fund_amount >= hard_cap)
+    }
 }
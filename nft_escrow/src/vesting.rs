@@ -0,0 +1,89 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Set vesting schedule of pre-mint allocation before activation, caller should be admin.
+    /// Vesting starts when `close_project` mints the allocation to escrow.
+    pub fn set_pre_mint_vesting(&mut self, schedule: Option<VestingSchedule>) {
+        self.assert_role(Role::Admin);
+        assert_eq!(self.start_timestamp, 0, "{}", ERR15_ALREADY_ACTIVATED);
+        if let Some(schedule) = &schedule {
+            assert!(schedule.duration > 0 && schedule.cliff <= schedule.duration, "{}", ERR70_INVALID_VESTING);
+        }
+
+        self.pre_mint_vesting = schedule;
+
+        log!("Pre-mint vesting {:?}", self.pre_mint_vesting);
+    }
+
+    /// Transfer vested project tokens of pre-mint allocation to owner
+    pub fn claim_vested(&mut self) -> Promise {
+        Self::require_owner();
+        let start_timestamp = self.pre_mint_vesting_start.unwrap_or_else(|| env::panic_str(ERR71_VESTING_NOT_STARTED));
+
+        let vested_amount = self.pre_mint_vesting.as_ref().unwrap()
+            .vested_amount(self.pre_mint_amount, start_timestamp, env::block_timestamp());
+        let amount = vested_amount.checked_sub(self.pre_mint_claimed_amount).unwrap();
+        assert!(amount > 0, "{}", ERR72_NOTHING_TO_CLAIM);
+
+        let from_index = self.pre_mint_token_offset.checked_add(self.pre_mint_claimed_amount).unwrap();
+        self.pre_mint_claimed_amount = vested_amount;
+
+        let transfer_promise = match self.project_token_type {
+            ProjectTokenType::NonFungible => {
+                let token_ids: Vec<TokenId> = (from_index..from_index + amount).map(|id| id.to_string()).collect();
+                ext_nft_collection::ext(self.project_token_id.clone().unwrap())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .with_attached_deposit(ONE_YOCTO)
+                    .nft_batch_transfer(
+                        self.internal_owner_id(),
+                        token_ids,
+                        Some("".to_string()),
+                    )
+            }
+            ProjectTokenType::Fungible => ext_fungible_token::ext(self.project_token_id.clone().unwrap())
+                .with_static_gas(Gas(5 * TGAS))
+                .with_attached_deposit(ONE_YOCTO)
+                .ft_transfer(
+                    self.internal_owner_id(),
+                    U128::from(amount),
+                    None,
+                ),
+        };
+
+        transfer_promise
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(Gas(5 * TGAS))
+                    .on_claim_vested(U128(amount))
+            )
+    }
+
+    #[private]
+    pub fn on_claim_vested(&mut self, amount: U128) -> bool {
+        if !is_promise_success() {
+            self.pre_mint_claimed_amount = self.pre_mint_claimed_amount.checked_sub(amount.0).unwrap();
+            return false;
+        }
+
+        log!("Claim vested {} {}", self.internal_owner_id(), amount.0);
+        true
+    }
+
+    pub fn get_vesting_info(&self) -> VestingInfo {
+        let vested_amount = match (&self.pre_mint_vesting, self.pre_mint_vesting_start) {
+            (Some(schedule), Some(start_timestamp)) =>
+                schedule.vested_amount(self.pre_mint_amount, start_timestamp, env::block_timestamp()),
+            _ => 0,
+        };
+
+        VestingInfo {
+            schedule: self.pre_mint_vesting.clone(),
+            start_timestamp: self.pre_mint_vesting_start,
+            total_amount: U128(self.pre_mint_amount),
+            vested_amount: U128(vested_amount),
+            claimed_amount: U128(self.pre_mint_claimed_amount),
+            claimable_amount: U128(vested_amount.saturating_sub(self.pre_mint_claimed_amount)),
+        }
+    }
+}
@@ -16,6 +16,10 @@ impl Contract {
 
     pub fn get_fund_threshold(&self) -> Balance { self.fund_threshold }
 
+    pub fn get_fund_hard_cap(&self) -> Option<Balance> { self.fund_hard_cap }
+
+    pub fn get_sale_end_timestamp(&self) -> Option<u64> { self.sale_end_timestamp }
+
     pub fn get_total_fund_amount(&self) -> Balance { self.total_fund_amount }
 
     pub fn get_pre_mint_amount(&self) -> Balance { self.pre_mint_amount }
@@ -37,4 +41,122 @@ impl Contract {
     pub fn get_converted_amount(&self) -> Balance { self.converted_amount }
 
     pub fn get_circulating_supply(&self) -> Balance { self.circulating_supply }
-}
\ No newline at end of file
+
+    pub fn get_phase(&self) -> Phase {
+        if self.halted_timestamp.is_some() {
+            return Phase::Refunding;
+        }
+        if self.start_timestamp == 0 {
+            return if self.closed_step == ClosedStep::None { Phase::NotActivated } else { Phase::Closed };
+        }
+        if self.tp_timestamp == 0 {
+            return if self.is_sale_ended() { Phase::Refunding } else { Phase::Funding };
+        }
+
+        let buffer_end = self.get_buffer_end_timestamp().unwrap();
+        let conversion_end = self.get_conversion_end_timestamp().unwrap();
+        let now = env::block_timestamp();
+        if now < buffer_end {
+            Phase::Buffer
+        } else if now <= conversion_end {
+            Phase::Conversion
+        } else {
+            Phase::Closed
+        }
+    }
+
+    pub fn get_buffer_end_timestamp(&self) -> Option<u64> {
+        if self.tp_timestamp == 0 {
+            return None;
+        }
+        Some(self.tp_timestamp.checked_add(self.buffer_period).unwrap())
+    }
+
+    pub fn get_conversion_end_timestamp(&self) -> Option<u64> {
+        self.get_buffer_end_timestamp()
+            .map(|buffer_end| buffer_end.checked_add(self.conversion_period).unwrap())
+    }
+
+    pub fn get_project_info(&self) -> ProjectInfo {
+        let phase = self.get_phase();
+        let is_claimable = self.tp_timestamp > 0 && phase == Phase::Closed && self.halted_timestamp.is_none();
+
+        ProjectInfo {
+            owner_id: Self::slot_owner().read(),
+            name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            treasury_id: self.treasury_id.clone(),
+            treasury_fee: self.treasury_fee,
+            finder_id: self.finder_id.clone(),
+            finder_fee: self.finder_fee,
+            project_token_type: self.project_token_type.clone(),
+            project_token_id: self.project_token_id.clone(),
+            stable_coin_id: self.stable_coin_id.clone(),
+            stable_coin_decimals: self.stable_coin_decimals,
+            curve_type: self.curve_type.clone(),
+            curve_args: self.curve_args.clone(),
+            pt_media_uri: self.pt_media_uri.clone(),
+            fund_threshold: U128(self.fund_threshold),
+            fund_hard_cap: self.fund_hard_cap.map(U128),
+            state: self.state.clone(),
+            closed_step: self.closed_step.clone(),
+            phase,
+            start_timestamp: self.start_timestamp,
+            tp_timestamp: self.tp_timestamp,
+            buffer_period: self.buffer_period,
+            conversion_period: self.conversion_period,
+            sale_end_timestamp: self.sale_end_timestamp,
+            buffer_end_timestamp: self.get_buffer_end_timestamp(),
+            conversion_end_timestamp: self.get_conversion_end_timestamp(),
+            current_price: U128(self.calculate_buy_proxy_token(U128(1))),
+            pt_max_supply: U128(self.pt_max_supply),
+            pt_all_total_supply: U128(self.pt_all_total_supply),
+            pre_mint_amount: U128(self.pre_mint_amount),
+            circulating_supply: U128(self.circulating_supply),
+            converted_amount: U128(self.converted_amount),
+            total_fund_amount: U128(self.total_fund_amount),
+            claimed_fund_amount: U128(self.claimed_fund_amount),
+            claimed_finder_fee: U128(self.claimed_finder_fee),
+            claimable_fund: U128(if is_claimable {
+                self.internal_available_fund()
+            } else {
+                0
+            }),
+            claimable_finder_fee: U128(if is_claimable {
+                self.internal_total_finder_fee().saturating_sub(self.claimed_finder_fee + self.pending_finder_fee_claim)
+            } else {
+                0
+            }),
+        }
+    }
+
+    pub fn get_account_position(&self, account_id: AccountId) -> AccountPosition {
+        let token_ids = self.pt_tokens_for_owner(account_id.clone());
+        let history = self.internal_account_history(&account_id);
+        let sell_value = if token_ids.is_empty() { 0 } else { self.calculate_sell_proxy_token(token_ids.clone()) };
+
+        let phase = self.get_phase();
+        let is_running = self.state == RunningState::Running && self.halted_timestamp.is_none();
+        let can_sell = is_running
+            && sell_value > 0
+            && matches!(phase, Phase::Funding | Phase::Buffer | Phase::Conversion | Phase::Refunding);
+        let can_convert = is_running
+            && !token_ids.is_empty()
+            && matches!(phase, Phase::Conversion | Phase::Closed)
+            && self.tp_timestamp > 0;
+        let can_halt_refund = self.halted_timestamp.is_some() && self.internal_vote_weight(&account_id, None) > 0;
+
+        AccountPosition {
+            account_id,
+            token_count: U128(token_ids.len() as u128),
+            token_ids,
+            sell_value: U128(sell_value),
+            paid_amount: U128(history.paid_amount),
+            refunded_amount: U128(history.refunded_amount),
+            converted_amount: U128(history.converted_amount),
+            can_sell,
+            can_convert,
+            can_refund: (can_sell && phase == Phase::Refunding) || can_halt_refund,
+        }
+    }
+}
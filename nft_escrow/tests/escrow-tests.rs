@@ -1,15 +1,25 @@
 mod helpers;
 
-use near_sdk::json_types::U128;
+use near_sdk::borsh::BorshSerialize;
+use near_sdk::json_types::{Base64VecU8, U128};
 use serde_json::json;
+use std::collections::HashMap;
 use workspaces::prelude::*;
+use workspaces::operations::Function;
+use workspaces::network::Sandbox;
 use workspaces::{Account, Contract, DevNetwork, Worker, AccountId};
 use helpers::*;
 
 const FUNGIBLE_TOKEN_CODE: &[u8] = include_bytes!("../../target/wasm32-unknown-unknown/release/ft_token.wasm");
 const NFT_ESCROW_CODE: &[u8] = include_bytes!("../../target/wasm32-unknown-unknown/release/nft_escrow_sc.wasm");
 const STORAGE_BYTE_COST: u128 = 10_000_000_000_000_000_000;
-const DEPOSIT_ONE_PT_MINT: u128 = 415 * STORAGE_BYTE_COST;
+const DEPOSIT_ONE_PT_MINT: u128 = 640 * STORAGE_BYTE_COST;
+const DEPOSIT_PT_OWNER: u128 = 250 * STORAGE_BYTE_COST;
+const STORAGE_ACCOUNT_COST: u128 = DEPOSIT_PT_OWNER + 700 * STORAGE_BYTE_COST;
+const ACTIVATION_DEPOSIT: u128 = 600_000 * STORAGE_BYTE_COST + FT_STORAGE_DEPOSIT;
+const FT_STORAGE_DEPOSIT: u128 = 125 * STORAGE_BYTE_COST;
+const ESCROW_STORAGE_DEPOSIT: u128 = 300_000 * STORAGE_BYTE_COST;
+const ONE_YEAR: u64 = 365 * 86400 * 1_000_000_000;
 
 fn parse_unit_with_decimals(amount: u128, decimals: u8) -> u128 {
     return amount * 10u128.pow(decimals as u32)
@@ -115,9 +125,127 @@ async fn init(
         .transact()
         .await?;
 
+    // buyers pay proxy token storage
+    for account in [&owner, &alice] {
+        account
+            .call(&worker, escrow_contract.id(), "storage_deposit")
+            .args_json((Option::<AccountId>::None, Option::<bool>::None))?
+            .deposit(ESCROW_STORAGE_DEPOSIT)
+            .max_gas()
+            .transact()
+            .await?;
+    }
+
     Ok((escrow_contract, stable_coin_contract, owner, alice, bob, finder, treasury, one_coin))
 }
 
+/// Deploy escrow with `V0` state of activated `escrow_contract` and migrate it.
+/// Proxy token balances are copied, indexes added after `V0` (tokens per owner, vote checkpoints, storage accounts) are not.
+/// Project token of the copy is a fungible token owned by it.
+async fn init_v0_escrow(
+    worker: &Worker<Sandbox>,
+    escrow_contract: &Contract,
+    stable_coin_contract: &Contract,
+    owner: &Account,
+) -> anyhow::Result<Contract> {
+    let project_info = escrow_contract.call(worker, "get_project_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    let json_u128 = |key: &str| project_info[key].as_str().unwrap().parse::<u128>().unwrap();
+    let json_u64 = |key: &str| project_info[key].as_u64().unwrap();
+
+    let escrow_v0 = worker.dev_deploy(NFT_ESCROW_CODE).await?;
+
+    let project_token = worker.dev_deploy(FUNGIBLE_TOKEN_CODE).await?;
+    let res = project_token
+        .call(worker, "new")
+        .args_json((escrow_v0.id(), NAME, SYMBOL, 24u8))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // proxy token supplies and balances, `StorageKey` variants 0..=2 are unchanged since `V0`
+    let balances_prefix = pt_balances_prefix();
+    let keys_prefix = [balances_prefix.clone(), b"k".to_vec()].concat();
+    let mut pt_count = 0u64;
+    for (key, value) in worker.view_state(escrow_contract.id(), None).await? {
+        if matches!(key.first(), Some(0..=2)) {
+            if key.starts_with(&keys_prefix) {
+                pt_count += 1;
+            }
+            worker.patch_state(escrow_v0.id(), &key, &value).await?;
+        }
+    }
+
+    let state = EscrowStateV0 {
+        owner_id: owner.id().to_string(),
+        name: NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        treasury_id: project_info["treasury_id"].as_str().unwrap().to_string(),
+        treasury_fee: json_u64("treasury_fee") as u32,
+        finder_id: project_info["finder_id"].as_str().map(String::from),
+        finder_fee: json_u64("finder_fee") as u32,
+        project_token_type: 1,
+        project_token_id: Some(project_token.id().to_string()),
+        fund_threshold: json_u128("fund_threshold"),
+        start_timestamp: json_u64("start_timestamp"),
+        tp_timestamp: json_u64("tp_timestamp"),
+        buffer_period: json_u64("buffer_period"),
+        conversion_period: json_u64("conversion_period"),
+        stable_coin_id: stable_coin_contract.id().to_string(),
+        stable_coin_decimals: 24,
+        total_fund_amount: json_u128("total_fund_amount"),
+        claimed_fund_amount: 0,
+        claimed_finder_fee: 0,
+        pre_mint_amount: json_u128("pre_mint_amount"),
+        converted_amount: json_u128("converted_amount"),
+        circulating_supply: json_u128("circulating_supply"),
+        curve_type: CurveType::Horizontal,
+        curve_args: CurveArgs { arg_a: Some(100u128), arg_b: None, arg_c: None, arg_d: None },
+        state: 0,
+        closed_step: 0,
+        pt_media_uri: NFT_BLANK_URI.to_string(),
+        pt_max_supply: json_u128("pt_max_supply"),
+        pt_all_total_supply: json_u128("pt_all_total_supply"),
+        pt_total_supply: pt_total_supply_prefix(),
+        pt_balances_per_token: (
+            [balances_prefix.clone(), b"i".to_vec()].concat(),
+            (pt_count, keys_prefix),
+            (pt_count, [balances_prefix, b"v".to_vec()].concat()),
+        ),
+    };
+    worker.patch_state(escrow_v0.id(), b"STATE", &state.try_to_vec()?).await?;
+
+    // reserve of copied escrow
+    stable_coin_contract
+        .call(worker, "storage_deposit")
+        .args_json((escrow_v0.id(), Option::<bool>::None))?
+        .deposit(125 * STORAGE_BYTE_COST)
+        .max_gas()
+        .transact()
+        .await?;
+    let res = owner
+        .call(worker, stable_coin_contract.id(), "ft_transfer")
+        .args_json((escrow_v0.id(), U128(state.total_fund_amount), Option::<String>::None))?
+        .deposit(1)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let res = escrow_v0
+        .call(worker, "migrate")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    Ok(escrow_v0)
+}
+
 #[tokio::test]
 async fn test_active_nft_project() -> anyhow::Result<()> {
     let worker = workspaces::sandbox().await?;
@@ -125,7 +253,8 @@ async fn test_active_nft_project() -> anyhow::Result<()> {
 
     let res = owner
         .call(&worker, contract.id(), "active_nft_project".into())
-        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -141,11 +270,86 @@ async fn test_active_ft_project() -> anyhow::Result<()> {
 
     let res = owner
         .call(&worker, contract.id(), "active_ft_project".into())
-        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success() && res.failures().is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_active_project_with_token_prefix() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (contract, _, owner, _, _, finder, _, _) = init(&worker).await?;
+
+    // invalid account id
+    let res = owner
+        .call(&worker, contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Some("Theia #1")))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let res = owner
+        .call(&worker, contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Some("tcn1")))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success() && res.failures().is_empty());
+
+    let project_token_id = contract.call(&worker, "get_project_token_id")
+        .view()
+        .await?
+        .json::<AccountId>()?;
+    assert_eq!(project_token_id.to_string(), format!("tcn1.{}", contract.id()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_activation_deposit() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (contract, _, owner, _, _, finder, _, _) = init(&worker).await?;
+
+    // deposit should cover project token account
+    let res = owner
+        .call(&worker, contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT / 2)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // excess deposit is refunded
+    let balance_before = worker.view_account(owner.id()).await?.balance;
+    let res = owner
+        .call(&worker, contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT * 2)
         .max_gas()
         .transact()
         .await?;
     assert!(res.is_success() && res.failures().is_empty());
+    let balance_after = worker.view_account(owner.id()).await?.balance;
+    assert!(balance_before - balance_after < ACTIVATION_DEPOSIT + 10_000 * STORAGE_BYTE_COST);
+
+    // already activated
+    let res = owner
+        .call(&worker, contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Some("tcn2")))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
 
     Ok(())
 }
@@ -177,7 +381,8 @@ async fn test_auction_curve_horizontal() -> anyhow::Result<()> {
     // active project
     owner
         .call(&worker, escrow_contract.id(), "active_nft_project")
-        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -278,7 +483,8 @@ async fn test_auction_curve_linear() -> anyhow::Result<()> {
     // active project
     owner
         .call(&worker, escrow_contract.id(), "active_nft_project")
-        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -383,7 +589,8 @@ async fn test_auction_curve_sigmoidal() -> anyhow::Result<()> {
     // active project
     owner
         .call(&worker, escrow_contract.id(), "active_nft_project")
-        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -468,7 +675,8 @@ async fn test_revert_nft_buy() -> anyhow::Result<()> {
     // active project
     let _res = owner
         .call(&worker, escrow_contract.id(), "active_nft_project")
-        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -534,7 +742,8 @@ async fn test_nft_buy() -> anyhow::Result<()> {
     // active project
     let _res = owner
         .call(&worker, escrow_contract.id(), "active_nft_project")
-        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -565,6 +774,15 @@ async fn test_nft_buy() -> anyhow::Result<()> {
     println!("buy: {:?}", res);
     assert!(res.is_success() && res.failures().is_empty());
 
+    // treasury claims fee accrued by buy
+    let res = treasury
+        .call(&worker, escrow_contract.id(), "claim_treasury_fee")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
     let balance = stable_coin_contract
         .view(
             &worker,
@@ -601,7 +819,8 @@ async fn test_ft_buy() -> anyhow::Result<()> {
     // active project
     let _res = owner
         .call(&worker, escrow_contract.id(), "active_ft_project")
-        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -632,6 +851,15 @@ async fn test_ft_buy() -> anyhow::Result<()> {
     assert!(_res.is_success());
     // println!("buy: {:?}", _res);
 
+    // treasury claims fee accrued by buy
+    let res = treasury
+        .call(&worker, escrow_contract.id(), "claim_treasury_fee")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
     let balance = stable_coin_contract
         .view(
             &worker,
@@ -667,7 +895,8 @@ async fn test_sell() -> anyhow::Result<()> {
     // active project
     let _res = owner
         .call(&worker, escrow_contract.id(), "active_nft_project")
-        .args_json((NFT_BLANK_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((NFT_BLANK_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -745,7 +974,8 @@ async fn test_nft_convert() -> anyhow::Result<()> {
     // active project
     owner
         .call(&worker, escrow_contract.id(), "active_nft_project")
-        .args_json((NFT_BLANK_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((NFT_BLANK_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -823,7 +1053,8 @@ async fn test_revert_ft_convert() -> anyhow::Result<()> {
 
     let res = owner
         .call(&worker, escrow_contract.id(), "active_ft_project".into())
-        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -887,7 +1118,8 @@ async fn test_ft_convert() -> anyhow::Result<()> {
 
     owner
         .call(&worker, escrow_contract.id(), "active_ft_project".into())
-        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -960,7 +1192,8 @@ async fn test_claim_fund() -> anyhow::Result<()> {
 
     owner
         .call(&worker, escrow_contract.id(), "active_ft_project".into())
-        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -1065,7 +1298,7 @@ async fn test_close_nft_project() -> anyhow::Result<()> {
     owner
         .call(&worker, escrow_contract.id(), "pre_mint")
         .args(json!({"amount": PRE_MINT_AMOUNT}).to_string().as_bytes().to_vec())
-        .deposit(PRE_MINT_AMOUNT.0 * DEPOSIT_ONE_PT_MINT)
+        .deposit(PRE_MINT_AMOUNT.0 * DEPOSIT_ONE_PT_MINT + DEPOSIT_PT_OWNER)
         .max_gas()
         .transact()
         .await?;
@@ -1073,7 +1306,8 @@ async fn test_close_nft_project() -> anyhow::Result<()> {
     // active project
     owner
         .call(&worker, escrow_contract.id(), "active_nft_project")
-        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -1152,7 +1386,8 @@ async fn test_convert_after_closing_nft_project() -> anyhow::Result<()> {
     // active project
     owner
         .call(&worker, escrow_contract.id(), "active_nft_project")
-        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
         .max_gas()
         .transact()
         .await?;
@@ -1231,6 +1466,2869 @@ async fn test_convert_after_closing_nft_project() -> anyhow::Result<()> {
 }
 
 
+#[tokio::test]
+async fn test_sale_hard_cap() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, finder, _, _) = init(&worker).await?;
+
+    // set hard cap to funding threshold
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_sale_limits")
+        .args_json((Some(FUND_THRESHOLD), Option::<u64>::None))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let phase = escrow_contract.call(&worker, "get_phase")
+        .view()
+        .await?
+        .json::<Phase>()?;
+    assert_eq!(phase, Phase::NotActivated);
+
+    // active project
+    owner
+        .call(&worker, escrow_contract.id(), "active_nft_project")
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let phase = escrow_contract.call(&worker, "get_phase")
+        .view()
+        .await?
+        .json::<Phase>()?;
+    assert_eq!(phase, Phase::Funding);
+
+    // buy proxy token over hard cap
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let total_fund_amount = escrow_contract.call(&worker, "get_total_fund_amount")
+        .view()
+        .await?
+        .json::<u128>()?;
+    assert_eq!(total_fund_amount, 0);
+
+    // buy proxy token under hard cap
+    let amount = U128::from(2u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+
+    let res = alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success() && res.failures().is_empty());
+
+    let total_fund_amount = escrow_contract.call(&worker, "get_total_fund_amount")
+        .view()
+        .await?
+        .json::<u128>()?;
+    assert_eq!(total_fund_amount, coin_amount * (100u128 - PROTOCOL_FEE as u128)/100u128);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_project_info() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, _, owner, _, _, finder, _, one_coin) = init(&worker).await?;
+
+    // active project
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project")
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let info = escrow_contract.call(&worker, "get_project_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+
+    assert_eq!(info["phase"], json!("Funding"));
+    assert_eq!(info["project_token_type"], json!("Fungible"));
+    assert_eq!(info["fund_threshold"], json!(FUND_THRESHOLD));
+    assert_eq!(info["finder_id"], json!(finder.id()));
+    assert_eq!(info["current_price"], json!(U128(100u128 * one_coin)));
+    assert_eq!(info["buffer_end_timestamp"], json!(null));
+    assert_eq!(info["claimable_fund"], json!(U128(0)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_account_position() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, finder, _, _) = init(&worker).await?;
+
+    // active project
+    owner
+        .call(&worker, escrow_contract.id(), "active_nft_project")
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Option::<HashMap<String, u32>>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // buy proxy token
+    let amount = U128::from(5u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let position = escrow_contract
+        .view(
+            &worker,
+            "get_account_position",
+            json!({
+                "account_id": alice.id()
+            }).to_string().into_bytes()
+        )
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(position["token_count"], json!(amount));
+    assert_eq!(position["paid_amount"], json!(U128(coin_amount)));
+    assert_eq!(position["can_sell"], json!(true));
+    assert_eq!(position["can_convert"], json!(false));
+
+    // sell proxy token
+    let res = alice
+        .call(&worker, escrow_contract.id(), "sell".into())
+        .args(json!({"token_ids": vec![4.to_string()]}).to_string().as_bytes().to_vec())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let position = escrow_contract
+        .view(
+            &worker,
+            "get_account_position",
+            json!({
+                "account_id": alice.id()
+            }).to_string().into_bytes()
+        )
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(position["token_count"], json!(U128(4u128)));
+    assert_ne!(position["refunded_amount"], json!(U128(0)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ownership_transfer() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, _, owner, alice, bob, _, _, _) = init(&worker).await?;
+
+    // propose alice
+    let res = owner
+        .call(&worker, escrow_contract.id(), "own_propose_owner")
+        .args_json(json!({"account_id": alice.id()}))?
+        .deposit(1)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // bob can not accept
+    let res = bob
+        .call(&worker, escrow_contract.id(), "own_accept_owner")
+        .args_json(json!({}))?
+        .deposit(1)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // alice accepts
+    let res = alice
+        .call(&worker, escrow_contract.id(), "own_accept_owner")
+        .args_json(json!({}))?
+        .deposit(1)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let owner_id = escrow_contract.call(&worker, "own_get_owner")
+        .view()
+        .await?
+        .json::<Option<AccountId>>()?;
+    assert_eq!(owner_id.as_ref(), Some(alice.id()));
+
+    // ownership can not be renounced
+    let res = alice
+        .call(&worker, escrow_contract.id(), "own_renounce_owner")
+        .args_json(json!({}))?
+        .deposit(1)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let info = escrow_contract.call(&worker, "get_project_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(info["owner_id"], json!(alice.id()));
+
+    // previous owner lost owner-only actions
+    let res = owner
+        .call(&worker, escrow_contract.id(), "grant_role")
+        .args_json(json!({"account_id": bob.id(), "role": "Admin"}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let proposed_owner_id = escrow_contract.call(&worker, "own_get_proposed_owner")
+        .view()
+        .await?
+        .json::<Option<AccountId>>()?;
+    assert!(proposed_owner_id.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_roles() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, _, owner, alice, bob, _, _, _) = init(&worker).await?;
+
+    // grant pauser role to alice
+    let res = owner
+        .call(&worker, escrow_contract.id(), "grant_role")
+        .args_json(json!({"account_id": alice.id(), "role": "Pauser"}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // bob can not pause
+    let res = bob
+        .call(&worker, escrow_contract.id(), "pause_contract")
+        .args_json(json!({}))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // alice pauses
+    let res = alice
+        .call(&worker, escrow_contract.id(), "pause_contract")
+        .args_json(json!({}))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // pauser can not claim fund
+    let res = alice
+        .call(&worker, escrow_contract.id(), "claim_fund")
+        .args_json(json!({"to": alice.id(), "amount": U128(1)}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // revoke pauser role from alice
+    owner
+        .call(&worker, escrow_contract.id(), "revoke_role")
+        .args_json(json!({"account_id": alice.id(), "role": "Pauser"}))?
+        .max_gas()
+        .transact()
+        .await?;
+
+    let granted = escrow_contract
+        .view(
+            &worker,
+            "is_role_granted",
+            json!({
+                "account_id": alice.id(),
+                "role": "Pauser"
+            }).to_string().into_bytes()
+        )
+        .await?
+        .json::<bool>()?;
+    assert!(!granted);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_multisig_fund_release() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, bob, finder, _, _) = init(&worker).await?;
+
+    // 2 of 2 multisig
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_fund_release_mode")
+        .args_json(json!({
+            "mode": {"Multisig": {"signers": [alice.id(), bob.id()], "threshold": 2}},
+            "proposal_period": TEN_MINUTES as u64 * 10
+        }))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    //buy proxy token
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // pass conversion period
+    worker.fast_forward(1200).await?;
+
+    let total_fund_amount =
+        escrow_contract.call(&worker, "get_total_fund_amount")
+            .view()
+            .await?
+            .json::<u128>()?;
+    let release_amount = U128(total_fund_amount / 2);
+
+    // direct claim is not allowed
+    let res = owner
+        .call(&worker, escrow_contract.id(), "claim_fund")
+        .args_json(json!({"to": owner.id(), "amount": release_amount}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // propose
+    let proposal_id = owner
+        .call(&worker, escrow_contract.id(), "propose_fund_release")
+        .args_json(json!({"to": owner.id(), "amount": release_amount}))?
+        .max_gas()
+        .transact()
+        .await?
+        .json::<u64>()?;
+
+    // first approval
+    let res = alice
+        .call(&worker, escrow_contract.id(), "approve_fund_release")
+        .args_json(json!({"proposal_id": proposal_id}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let info = escrow_contract.call(&worker, "get_project_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(info["claimed_fund_amount"], json!(U128(0)));
+
+    // second approval releases fund
+    let res = bob
+        .call(&worker, escrow_contract.id(), "approve_fund_release")
+        .args_json(json!({"proposal_id": proposal_id}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let info = escrow_contract.call(&worker, "get_project_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(info["claimed_fund_amount"], json!(release_amount));
+
+    let proposal = escrow_contract
+        .view(
+            &worker,
+            "get_fund_release_proposal",
+            json!({
+                "proposal_id": proposal_id
+            }).to_string().into_bytes()
+        )
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(proposal["status"], json!("Executed"));
+
+    // failed release to unregistered bob keeps the rest of unlocked fund reserved
+    let fund_lock_info = escrow_contract.call(&worker, "get_fund_lock_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    let unlocked_fund = fund_lock_info["unlocked_fund"].as_str().unwrap().parse::<u128>()?;
+    let remain_amount = U128(unlocked_fund - release_amount.0);
+    let proposal_id = owner
+        .call(&worker, escrow_contract.id(), "propose_fund_release")
+        .args_json(json!({"to": bob.id(), "amount": remain_amount}))?
+        .max_gas()
+        .transact()
+        .await?
+        .json::<u64>()?;
+    for signer in [&alice, &bob] {
+        let res = signer
+            .call(&worker, escrow_contract.id(), "approve_fund_release")
+            .args_json(json!({"proposal_id": proposal_id}))?
+            .max_gas()
+            .transact()
+            .await?;
+        assert!(res.is_success());
+    }
+    let proposal = escrow_contract
+        .view(
+            &worker,
+            "get_fund_release_proposal",
+            json!({
+                "proposal_id": proposal_id
+            }).to_string().into_bytes()
+        )
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(proposal["status"], json!("Approved"));
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "propose_fund_release")
+        .args_json(json!({"to": owner.id(), "amount": U128(1)}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // cancelling approved proposal releases its reservation
+    let res = owner
+        .call(&worker, escrow_contract.id(), "cancel_fund_release")
+        .args_json(json!({"proposal_id": proposal_id}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    let res = owner
+        .call(&worker, escrow_contract.id(), "propose_fund_release")
+        .args_json(json!({"to": owner.id(), "amount": remain_amount}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_milestone_fund_release() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, bob, finder, _, _) = init(&worker).await?;
+
+    // 50% after bob approves, 50% far in the future
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_milestones")
+        .args_json(json!({
+            "milestones": [
+                {"title": "MVP", "percent": 5000, "unlock_timestamp": null, "requires_approval": true},
+                {"title": "Launch", "percent": 5000, "unlock_timestamp": u64::MAX, "requires_approval": false}
+            ],
+            "verifier_id": bob.id()
+        }))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    //buy proxy token
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // pass conversion period
+    worker.fast_forward(1200).await?;
+
+    let lock_info = escrow_contract.call(&worker, "get_fund_lock_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(lock_info["unlocked_fund"], json!(U128(0)));
+    let total_fund = lock_info["total_fund"].as_str().unwrap().parse::<u128>()?;
+
+    // locked fund can not be claimed
+    let res = owner
+        .call(&worker, escrow_contract.id(), "claim_fund")
+        .args_json(json!({"to": owner.id(), "amount": U128(total_fund / 2)}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // submit and approve first milestone
+    owner
+        .call(&worker, escrow_contract.id(), "submit_milestone")
+        .args_json(json!({"index": 0, "evidence": "https://theia.example/mvp"}))?
+        .max_gas()
+        .transact()
+        .await?;
+
+    let res = bob
+        .call(&worker, escrow_contract.id(), "approve_milestone")
+        .args_json(json!({"index": 0}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "claim_fund")
+        .args_json(json!({"to": owner.id(), "amount": U128(total_fund / 2)}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let lock_info = escrow_contract.call(&worker, "get_fund_lock_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(lock_info["claimed_fund"], json!(U128(total_fund / 2)));
+    assert_eq!(lock_info["locked_fund"], json!(U128(total_fund - total_fund / 2)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_halt_voting() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, bob, finder, _, _) = init(&worker).await?;
+
+    // 1 second voting period
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_halt_config")
+        .args_json(json!({"config": {"quorum": 2000, "threshold": 5000, "voting_period": 1_000_000_000u64}}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    //buy proxy token
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // account without proxy token can not propose
+    let res = bob
+        .call(&worker, escrow_contract.id(), "create_halt_proposal")
+        .args_json(json!({"reason": "rug"}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let proposal_id = alice
+        .call(&worker, escrow_contract.id(), "create_halt_proposal")
+        .args_json(json!({"reason": "project abandoned"}))?
+        .max_gas()
+        .transact()
+        .await?
+        .json::<u64>()?;
+
+    let res = alice
+        .call(&worker, escrow_contract.id(), "vote_halt")
+        .args_json(json!({"proposal_id": proposal_id, "support": true}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // double vote
+    let res = alice
+        .call(&worker, escrow_contract.id(), "vote_halt")
+        .args_json(json!({"proposal_id": proposal_id, "support": true}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    worker.fast_forward(100).await?;
+
+    let passed = bob
+        .call(&worker, escrow_contract.id(), "finalize_halt_proposal")
+        .args_json(json!({"proposal_id": proposal_id}))?
+        .max_gas()
+        .transact()
+        .await?
+        .json::<bool>()?;
+    assert!(passed);
+
+    let phase = escrow_contract.call(&worker, "get_phase")
+        .view()
+        .await?
+        .json::<Phase>()?;
+    assert_eq!(phase, Phase::Refunding);
+
+    // trading is stopped
+    let token_ids = escrow_contract
+        .view(
+            &worker,
+            "pt_tokens_for_owner",
+            json!({"account_id": alice.id()}).to_string().into_bytes(),
+        )
+        .await?
+        .json::<Vec<String>>()?;
+    let res = alice
+        .call(&worker, escrow_contract.id(), "sell")
+        .args_json(json!({"token_ids": token_ids}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let refund = escrow_contract
+        .view(
+            &worker,
+            "get_halt_refund",
+            json!({"account_id": alice.id()}).to_string().into_bytes(),
+        )
+        .await?
+        .json::<U128>()?;
+    let total_fund = escrow_contract.call(&worker, "get_total_fund_amount")
+        .view()
+        .await?
+        .json::<u128>()?;
+    assert_eq!(refund.0, total_fund);
+
+    let balance_before = stable_coin_contract
+        .view(
+            &worker,
+            "ft_balance_of",
+            json!({
+                "account_id": alice.id()
+            }).to_string().into_bytes()
+        )
+        .await?
+        .json::<U128>()?;
+
+    let res = alice
+        .call(&worker, escrow_contract.id(), "claim_halt_refund")
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let balance_after = stable_coin_contract
+        .view(
+            &worker,
+            "ft_balance_of",
+            json!({
+                "account_id": alice.id()
+            }).to_string().into_bytes()
+        )
+        .await?
+        .json::<U128>()?;
+    assert_eq!(balance_after.0 - balance_before.0, refund.0);
+
+    // refunded proxy tokens are burned
+    let token_ids = escrow_contract
+        .view(
+            &worker,
+            "pt_tokens_for_owner",
+            json!({"account_id": alice.id()}).to_string().into_bytes(),
+        )
+        .await?
+        .json::<Vec<String>>()?;
+    assert!(token_ids.is_empty());
+
+    // refund only once
+    let res = alice
+        .call(&worker, escrow_contract.id(), "claim_halt_refund")
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_owner_halt_refund() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, _, _, finder, _, _) = init(&worker).await?;
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_halt_config")
+        .args_json(json!({"config": {"quorum": 2000, "threshold": 5000, "voting_period": 1_000_000_000u64}}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // pre-mint
+    owner
+        .call(&worker, escrow_contract.id(), "pre_mint")
+        .args(json!({"amount": PRE_MINT_AMOUNT}).to_string().as_bytes().to_vec())
+        .deposit(PRE_MINT_AMOUNT.0 * DEPOSIT_ONE_PT_MINT + DEPOSIT_PT_OWNER)
+        .max_gas()
+        .transact()
+        .await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // owner buys on top of pre-mint
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+
+    owner
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // only bought proxy tokens are weighted
+    let weight = escrow_contract
+        .view(
+            &worker,
+            "get_vote_weight",
+            json!({"account_id": owner.id()}).to_string().into_bytes(),
+        )
+        .await?
+        .json::<U128>()?;
+    assert_eq!(weight, amount);
+
+    let proposal_id = owner
+        .call(&worker, escrow_contract.id(), "create_halt_proposal")
+        .args_json(json!({"reason": "project abandoned"}))?
+        .max_gas()
+        .transact()
+        .await?
+        .json::<u64>()?;
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "vote_halt")
+        .args_json(json!({"proposal_id": proposal_id, "support": true}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    worker.fast_forward(100).await?;
+
+    let passed = owner
+        .call(&worker, escrow_contract.id(), "finalize_halt_proposal")
+        .args_json(json!({"proposal_id": proposal_id}))?
+        .max_gas()
+        .transact()
+        .await?
+        .json::<bool>()?;
+    assert!(passed);
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "claim_halt_refund")
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // bought proxy tokens are burned, pre-minted ones are kept
+    let mut token_ids = escrow_contract
+        .view(
+            &worker,
+            "pt_tokens_for_owner",
+            json!({"account_id": owner.id()}).to_string().into_bytes(),
+        )
+        .await?
+        .json::<Vec<String>>()?;
+    token_ids.sort_by_key(|token_id| token_id.parse::<u128>().unwrap());
+    let pre_mint_ids: Vec<String> = (0..PRE_MINT_AMOUNT.0).map(|token_id| token_id.to_string()).collect();
+    assert_eq!(token_ids, pre_mint_ids);
+
+    let circulating_supply = escrow_contract.call(&worker, "get_circulating_supply")
+        .view()
+        .await?
+        .json::<u128>()?;
+    assert_eq!(circulating_supply, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pre_mint_vesting() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, finder, _, _) = init(&worker).await?;
+
+    // pre-mint
+    owner
+        .call(&worker, escrow_contract.id(), "pre_mint")
+        .args(json!({"amount": PRE_MINT_AMOUNT}).to_string().as_bytes().to_vec())
+        .deposit(PRE_MINT_AMOUNT.0 * DEPOSIT_ONE_PT_MINT + DEPOSIT_PT_OWNER)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // cliff is longer than duration
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_pre_mint_vesting")
+        .args_json(json!({"schedule": {"cliff": 2 * ONE_YEAR, "duration": ONE_YEAR}}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_pre_mint_vesting")
+        .args_json(json!({"schedule": {"cliff": ONE_YEAR, "duration": 2 * ONE_YEAR}}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // schedule can not be changed after activation
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_pre_mint_vesting")
+        .args_json(json!({"schedule": null}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    //buy proxy token
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // nothing to claim before closing
+    let res = owner
+        .call(&worker, escrow_contract.id(), "claim_vested")
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    worker.fast_forward(1200).await?;
+
+    // close project - pre-mint is held by escrow
+    let res = owner
+        .call(&worker, escrow_contract.id(), "close_project")
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let vesting_info = escrow_contract.call(&worker, "get_vesting_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    assert!(vesting_info["start_timestamp"].is_u64());
+    assert_eq!(vesting_info["total_amount"], json!(PRE_MINT_AMOUNT));
+    assert_eq!(vesting_info["vested_amount"], json!(U128(0)));
+
+    // still in cliff
+    let res = owner
+        .call(&worker, escrow_contract.id(), "claim_vested")
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_allocations() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, bob, finder, _, _) = init(&worker).await?;
+
+    // team allocation fully vested once threshold is reached, advisors after a year
+    let res = owner
+        .call(&worker, escrow_contract.id(), "add_allocation")
+        .args_json(json!({"name": "team", "beneficiary_id": bob.id(), "amount": U128(1000), "schedule": {"cliff": 0, "duration": 1}}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    let res = owner
+        .call(&worker, escrow_contract.id(), "add_allocation")
+        .args_json(json!({"name": "advisors", "beneficiary_id": alice.id(), "amount": U128(FT_MAX_SUPPLY.0), "schedule": {"cliff": ONE_YEAR, "duration": ONE_YEAR}}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // allocations are over max supply
+    let res = owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    owner
+        .call(&worker, escrow_contract.id(), "remove_allocation")
+        .args_json(json!({"index": 1}))?
+        .max_gas()
+        .transact()
+        .await?;
+    let res = owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // vesting starts when funding threshold is reached
+    let res = bob
+        .call(&worker, escrow_contract.id(), "claim_allocation")
+        .args_json(json!({"index": 0}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    //buy proxy token
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // only beneficiary can claim
+    let res = alice
+        .call(&worker, escrow_contract.id(), "claim_allocation")
+        .args_json(json!({"index": 0}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // register account
+    let project_token_id = escrow_contract.call(&worker, "get_project_token_id")
+        .view()
+        .await?
+        .json::<AccountId>()?;
+    bob
+        .call(&worker, &project_token_id, "storage_deposit")
+        .args_json((bob.id(), Option::<bool>::None))?
+        .deposit(125 * STORAGE_BYTE_COST)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let res = bob
+        .call(&worker, escrow_contract.id(), "claim_allocation")
+        .args_json(json!({"index": 0}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let balance = bob
+        .call(&worker, &project_token_id, "ft_balance_of")
+        .args_json((bob.id(),))?
+        .view()
+        .await?
+        .json::<U128>()?;
+    assert_eq!(balance.0, 1000);
+
+    let vesting_info = escrow_contract
+        .view(
+            &worker,
+            "get_allocation_vesting_info",
+            json!({"index": 0}).to_string().into_bytes(),
+        )
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(vesting_info["claimed_amount"], json!(U128(1000)));
+    assert_eq!(vesting_info["claimable_amount"], json!(U128(0)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_allocation_after_close() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, bob, finder, _, _) = init(&worker).await?;
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "add_allocation")
+        .args_json(json!({"name": "team", "beneficiary_id": bob.id(), "amount": U128(1000), "schedule": {"cliff": 0, "duration": 1}}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    //buy proxy token
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    worker.fast_forward(1200).await?;
+
+    // close project with unclaimed allocation - pre-mint, remaining proxies and allocation, ownership handover
+    for _ in 0..3 {
+        let res = owner
+            .call(&worker, escrow_contract.id(), "close_project")
+            .max_gas()
+            .transact()
+            .await?;
+        assert!(res.is_success());
+    }
+
+    let project_token_id = escrow_contract.call(&worker, "get_project_token_id")
+        .view()
+        .await?
+        .json::<AccountId>()?;
+    let res = owner
+        .call(&worker, &project_token_id, "own_accept_owner")
+        .deposit(1)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // remaining proxies and unclaimed allocation are held by escrow
+    let balance = bob
+        .call(&worker, &project_token_id, "ft_balance_of")
+        .args_json((escrow_contract.id(),))?
+        .view()
+        .await?
+        .json::<U128>()?;
+    assert_eq!(balance.0, amount.0 + 1000);
+
+    bob
+        .call(&worker, &project_token_id, "storage_deposit")
+        .args_json((bob.id(), Option::<bool>::None))?
+        .deposit(125 * STORAGE_BYTE_COST)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // allocation is transferred from escrow
+    let res = bob
+        .call(&worker, escrow_contract.id(), "claim_allocation")
+        .args_json(json!({"index": 0}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let balance = bob
+        .call(&worker, &project_token_id, "ft_balance_of")
+        .args_json((bob.id(),))?
+        .view()
+        .await?
+        .json::<U128>()?;
+    assert_eq!(balance.0, 1000);
+
+    let balance = bob
+        .call(&worker, &project_token_id, "ft_balance_of")
+        .args_json((escrow_contract.id(),))?
+        .view()
+        .await?
+        .json::<U128>()?;
+    assert_eq!(balance.0, amount.0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upgrade_project_token() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, _, owner, alice, _, finder, _, _) = init(&worker).await?;
+
+    // project token is not deployed yet
+    let res = owner
+        .call(&worker, escrow_contract.id(), "upgrade_project_token")
+        .args_json(json!({"code": Base64VecU8::from(FUNGIBLE_TOKEN_CODE.to_vec())}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let project_token_id = escrow_contract.call(&worker, "get_project_token_id")
+        .view()
+        .await?
+        .json::<AccountId>()?;
+
+    // only escrow owner can upgrade
+    let res = alice
+        .call(&worker, escrow_contract.id(), "upgrade_project_token")
+        .args_json(json!({"code": Base64VecU8::from(FUNGIBLE_TOKEN_CODE.to_vec())}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // escrow is the only caller allowed on project token
+    let res = alice
+        .call(&worker, &project_token_id, "upgrade")
+        .args_json(json!({"code": Base64VecU8::from(FUNGIBLE_TOKEN_CODE.to_vec())}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "upgrade_project_token")
+        .args_json(json!({"code": Base64VecU8::from(FUNGIBLE_TOKEN_CODE.to_vec())}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // project token state is kept
+    let token_owner = owner
+        .call(&worker, &project_token_id, "own_get_owner")
+        .view()
+        .await?
+        .json::<Option<AccountId>>()?;
+    assert_eq!(token_owner.as_ref(), Some(escrow_contract.id()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_external_ft_project() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, finder, _, _) = init(&worker).await?;
+
+    // deploy project token outside of escrow
+    let project_token_contract = worker.dev_deploy(FUNGIBLE_TOKEN_CODE).await?;
+    project_token_contract
+        .call(&worker, "new")
+        .args_json((owner.id(), String::from("Theia Token"), String::from("THT"), 1u8))?
+        .max_gas()
+        .transact()
+        .await?;
+    for account_id in [escrow_contract.id(), alice.id()] {
+        project_token_contract
+            .call(&worker, "storage_deposit")
+            .args_json((account_id, Option::<bool>::None))?
+            .deposit(125 * STORAGE_BYTE_COST)
+            .max_gas()
+            .transact()
+            .await?;
+    }
+    owner.call(&worker, project_token_contract.id(), "ft_mint")
+        .args_json((owner.id(), U128(1000)))?
+        .max_gas()
+        .transact()
+        .await?;
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "active_external_project")
+        .args_json((project_token_contract.id(), "Fungible", FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // no inventory to sell yet
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert_eq!(escrow_contract.call(&worker, "get_circulating_supply")
+        .view()
+        .await?
+        .json::<u128>()?, 0);
+
+    // transfer inventory
+    owner
+        .call(&worker, project_token_contract.id(), "ft_transfer_call")
+        .args_json((escrow_contract.id(), U128(1000), Option::<String>::None, String::from("")))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert_eq!(escrow_contract.call(&worker, "get_inventory_amount")
+        .view()
+        .await?
+        .json::<U128>()?, U128(1000));
+
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    worker.fast_forward(300).await?;
+
+    // convert from inventory
+    let token_ids = escrow_contract
+        .view(
+            &worker,
+            "pt_tokens_for_owner",
+            json!({"account_id": alice.id()}).to_string().into_bytes(),
+        )
+        .await?
+        .json::<Vec<String>>()?;
+    let res = alice
+        .call(&worker, escrow_contract.id(), "convert")
+        .args_json(json!({"token_ids": token_ids}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let balance = alice
+        .call(&worker, project_token_contract.id(), "ft_balance_of")
+        .args_json((alice.id(),))?
+        .view()
+        .await?
+        .json::<U128>()?;
+    assert_eq!(balance.0, 3);
+    assert_eq!(escrow_contract.call(&worker, "get_inventory_amount")
+        .view()
+        .await?
+        .json::<U128>()?, U128(997));
+
+    // inventory above remaining proxy tokens is recoverable
+    let res = owner
+        .call(&worker, escrow_contract.id(), "recover_ft")
+        .args_json((project_token_contract.id(), U128(998)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "recover_ft")
+        .args_json((project_token_contract.id(), U128(990)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success() && res.failures().is_empty());
+    assert_eq!(escrow_contract.call(&worker, "get_inventory_amount")
+        .view()
+        .await?
+        .json::<U128>()?, U128(7));
+    assert_eq!(owner
+        .call(&worker, project_token_contract.id(), "ft_balance_of")
+        .args_json((owner.id(),))?
+        .view()
+        .await?
+        .json::<U128>()?, U128(990));
+    assert_invariants(&worker, &escrow_contract).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_concurrent_operations() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, finder, _, _) = init(&worker).await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // owner buys 0..2, alice buys 3..5
+    let amount = U128::from(3u128);
+    for buyer in [&owner, &alice] {
+        let coin_amount = escrow_contract
+            .view(
+                &worker,
+                "calculate_buy_proxy_token",
+                json!({
+                "amount": amount
+            }).to_string().into_bytes(),
+            )
+            .await?
+            .json::<u128>()?;
+
+        let res = buyer
+            .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+            .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+            .deposit(1u128)
+            .max_gas()
+            .transact()
+            .await?;
+        assert!(res.is_success());
+    }
+
+    // sells of different accounts in parallel
+    let owner_sell = owner
+        .call(&worker, escrow_contract.id(), "sell")
+        .args_json(json!({"token_ids": vec!["0".to_string()]}))?
+        .max_gas()
+        .transact();
+    let alice_sell = alice
+        .call(&worker, escrow_contract.id(), "sell")
+        .args_json(json!({"token_ids": vec!["3".to_string()]}))?
+        .max_gas()
+        .transact();
+    let (owner_res, alice_res) = tokio::join!(owner_sell, alice_sell);
+    assert!(owner_res?.is_success());
+    assert!(alice_res?.is_success());
+
+    // second sell of the same account waits for the first callback
+    let res = alice
+        .batch(&worker, escrow_contract.id())
+        .call(Function::new("sell").args_json(json!({"token_ids": vec!["4".to_string()]}))?.gas(100_000_000_000_000))
+        .call(Function::new("sell").args_json(json!({"token_ids": vec!["5".to_string()]}))?.gas(100_000_000_000_000))
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+    assert_eq!(alice
+        .call(&worker, escrow_contract.id(), "pt_balance_of")
+        .args_json((alice.id(), vec!["4".to_string(), "5".to_string()]))?
+        .view()
+        .await?
+        .json::<Vec<u128>>()?, vec![1u128, 1u128]);
+
+    let pending = escrow_contract.call(&worker, "get_pending_operations")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(pending["sell_refund"], "0");
+    assert_eq!(pending["sell_amount"], "0");
+    let is_pending = escrow_contract
+        .view(&worker, "is_account_pending", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<bool>()?;
+    assert!(!is_pending);
+
+    // pass buffer and conversion period
+    worker.fast_forward(1200).await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "grant_role")
+        .args_json(json!({"account_id": alice.id(), "role": "FundClaimer"}))?
+        .max_gas()
+        .transact()
+        .await?;
+
+    let project_info = escrow_contract.call(&worker, "get_project_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    let claimable_fund = project_info["claimable_fund"].as_str().unwrap().parse::<u128>()?;
+    let claim_amount = claimable_fund * 2 / 3;
+
+    // parallel claims over claimable fund, only one is reserved
+    let owner_claim = owner
+        .call(&worker, escrow_contract.id(), "claim_fund")
+        .args_json(json!({"to": owner.id(), "amount": U128(claim_amount)}))?
+        .max_gas()
+        .transact();
+    let alice_claim = alice
+        .call(&worker, escrow_contract.id(), "claim_fund")
+        .args_json(json!({"to": alice.id(), "amount": U128(claim_amount)}))?
+        .max_gas()
+        .transact();
+    let (owner_res, alice_res) = tokio::join!(owner_claim, alice_claim);
+    assert!(owner_res?.is_success() != alice_res?.is_success());
+
+    let project_info = escrow_contract.call(&worker, "get_project_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(project_info["claimed_fund_amount"], claim_amount.to_string());
+    assert_eq!(project_info["claimable_fund"], (claimable_fund - claim_amount).to_string());
+
+    // same for finder fee claims in one block
+    let claimable_finder_fee = project_info["claimable_finder_fee"].as_str().unwrap().parse::<u128>()?;
+    let res = owner
+        .batch(&worker, escrow_contract.id())
+        .call(Function::new("claim_finder_fee").args_json(json!({"amount": U128(claimable_finder_fee)}))?.gas(50_000_000_000_000))
+        .call(Function::new("claim_finder_fee").args_json(json!({"amount": U128(claimable_finder_fee)}))?.gas(50_000_000_000_000))
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let finder_balance = stable_coin_contract
+        .view(
+            &worker,
+            "ft_balance_of",
+            json!({
+                "account_id": finder.id()
+            }).to_string().into_bytes()
+        )
+        .await?
+        .json::<U128>()?;
+    assert_eq!(finder_balance.0, 0);
+
+    Ok(())
+}
+
+async fn assert_invariants(worker: &Worker<impl DevNetwork>, escrow_contract: &Contract) -> anyhow::Result<()> {
+    let report = escrow_contract.call(worker, "check_invariants")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(report["is_valid"], true, "{}", report["violations"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invariants_random_operations() -> anyhow::Result<()> {
+    // fixed seeds keep failures reproducible
+    for seed in [0x2545_f491_4f6c_dd1d, 0x9e37_79b9_7f4a_7c15, 0xd1b5_4a32_d192_ed03, 0x0123_4567_89ab_cdef] {
+        run_random_operations(seed).await?;
+    }
+
+    Ok(())
+}
+
+/// Buy, sell and convert by random accounts, operations allowed by account position should succeed
+async fn run_random_operations(seed: u64) -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, finder, _, _) = init(&worker).await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert_invariants(&worker, &escrow_contract).await?;
+
+    let project_token_id = escrow_contract.call(&worker, "get_project_token_id")
+        .view()
+        .await?
+        .json::<AccountId>()?;
+    for account in [&owner, &alice] {
+        account
+            .call(&worker, &project_token_id, "storage_deposit")
+            .args_json((account.id(), Option::<bool>::None))?
+            .deposit(125 * STORAGE_BYTE_COST)
+            .max_gas()
+            .transact()
+            .await?;
+    }
+
+    let mut state = seed;
+    let mut next = |n: u64| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) % n
+    };
+
+    for step in 0..24 {
+        // pass buffer period halfway to allow conversions
+        if step == 12 {
+            worker.fast_forward(400).await?;
+        }
+
+        let account = if next(2) == 0 { &owner } else { &alice };
+        let position = escrow_contract
+            .view(&worker, "get_account_position", json!({"account_id": account.id()}).to_string().into_bytes())
+            .await?
+            .json::<serde_json::Value>()?;
+        let owned_ids: Vec<String> = position["token_ids"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|token_id| token_id.as_str().unwrap().to_string())
+            .collect();
+        let owned_count = owned_ids.len() as u128;
+
+        let action = next(3);
+        let (expected_count, expected_converted) = if action == 0 || owned_ids.is_empty() {
+            let amount = U128(1 + next(3) as u128);
+            let coin_amount = escrow_contract
+                .view(&worker, "calculate_buy_proxy_token", json!({"amount": amount}).to_string().into_bytes())
+                .await?
+                .json::<u128>()?;
+            account
+                .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+                .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+                .deposit(1u128)
+                .max_gas()
+                .transact()
+                .await?;
+            // sale is ongoing during all steps
+            (owned_count + amount.0, 0)
+        } else {
+            let count = 1 + next(owned_ids.len() as u64) as usize;
+            let token_ids: Vec<String> = owned_ids.into_iter().take(count).collect();
+            let (method, allowed) = if action == 1 {
+                ("sell", position["can_sell"].as_bool().unwrap())
+            } else {
+                ("convert", position["can_convert"].as_bool().unwrap())
+            };
+            // operations rejected in current phase should keep invariants too
+            let res = account
+                .call(&worker, escrow_contract.id(), method)
+                .args_json(json!({"token_ids": token_ids}))?
+                .max_gas()
+                .transact()
+                .await?;
+            assert_eq!(res.is_success(), allowed, "seed {:#x} step {} {}", seed, step, method);
+            match (allowed, method) {
+                (false, _) => (owned_count, 0),
+                (true, "sell") => (owned_count - count as u128, 0),
+                (true, _) => (owned_count - count as u128, count as u128),
+            }
+        };
+
+        let updated = escrow_contract
+            .view(&worker, "get_account_position", json!({"account_id": account.id()}).to_string().into_bytes())
+            .await?
+            .json::<serde_json::Value>()?;
+        assert_eq!(updated["token_count"], json!(U128(expected_count)), "seed {:#x} step {}", seed, step);
+        let converted = |position: &serde_json::Value| position["converted_amount"].as_str().unwrap().parse::<u128>().unwrap();
+        assert_eq!(converted(&updated), converted(&position) + expected_converted, "seed {:#x} step {}", seed, step);
+
+        assert_invariants(&worker, &escrow_contract).await?;
+    }
+
+    // reconcile with actual stable coin balance
+    let res = owner
+        .call(&worker, escrow_contract.id(), "reconcile_stable_coin")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    let report = res.json::<serde_json::Value>()?;
+    assert_eq!(report["is_valid"], true, "{}", report["violations"]);
+    assert!(report["stable_coin_balance"].is_string());
+
+    // only owner reconciles
+    let res = alice
+        .call(&worker, escrow_contract.id(), "reconcile_stable_coin")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recover_stray_tokens() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, _, _, _) = init(&worker).await?;
+
+    // unknown message returns stable coin
+    let alice_balance = stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?;
+    let res = alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(1000), Option::<String>::None, String::from("donate")))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    assert_eq!(stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, alice_balance);
+
+    // token sent by plain transfer stays in escrow
+    let stray_token_contract = worker.dev_deploy(FUNGIBLE_TOKEN_CODE).await?;
+    stray_token_contract
+        .call(&worker, "new")
+        .args_json((owner.id(), String::from("Stray Token"), String::from("STT"), 1u8))?
+        .max_gas()
+        .transact()
+        .await?;
+    for account_id in [escrow_contract.id(), alice.id()] {
+        stray_token_contract
+            .call(&worker, "storage_deposit")
+            .args_json((account_id, Option::<bool>::None))?
+            .deposit(125 * STORAGE_BYTE_COST)
+            .max_gas()
+            .transact()
+            .await?;
+    }
+    owner.call(&worker, stray_token_contract.id(), "ft_mint")
+        .args_json((alice.id(), U128(1000)))?
+        .max_gas()
+        .transact()
+        .await?;
+    alice
+        .call(&worker, stray_token_contract.id(), "ft_transfer")
+        .args_json((escrow_contract.id(), U128(1000), Option::<String>::None))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // only owner or treasury recovers
+    let res = alice
+        .call(&worker, escrow_contract.id(), "recover_ft")
+        .args_json((stray_token_contract.id(), U128(1000)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // stable coin reserve is not recoverable
+    let res = owner
+        .call(&worker, escrow_contract.id(), "recover_ft")
+        .args_json((stable_coin_contract.id(), U128(1)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // stable coin sent by plain transfer is recoverable above expected balance
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer")
+        .args_json((escrow_contract.id(), U128(1000), Option::<String>::None))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    let res = owner
+        .call(&worker, escrow_contract.id(), "recover_ft")
+        .args_json((stable_coin_contract.id(), U128(1001)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+    let owner_balance = stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": owner.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?;
+    let res = owner
+        .call(&worker, escrow_contract.id(), "recover_ft")
+        .args_json((stable_coin_contract.id(), U128(1000)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success() && res.failures().is_empty());
+    assert_eq!(stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": owner.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(owner_balance.0 + 1000));
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "recover_ft")
+        .args_json((stray_token_contract.id(), U128(1000)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success() && res.failures().is_empty());
+    assert_eq!(stray_token_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": owner.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(1000));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recover_stable_coin_during_buy() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, finder, _, one_coin) = init(&worker).await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // stray stable coin
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer")
+        .args_json((escrow_contract.id(), U128(1000), Option::<String>::None))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+    let change = 100 * one_coin;
+
+    // deposit and change of buy in flight are not recoverable
+    let buy = alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount + change), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact();
+    let recover = owner
+        .call(&worker, escrow_contract.id(), "recover_ft")
+        .args_json((stable_coin_contract.id(), U128(1000 + change)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact();
+    let (buy_res, recover_res) = tokio::join!(buy, recover);
+    assert!(buy_res?.is_success());
+    assert!(!recover_res?.is_success());
+
+    let pending_operations = escrow_contract.call(&worker, "get_pending_operations")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(pending_operations["buy_deposit"], json!(U128(0)));
+    assert_eq!(pending_operations["payout_transfer"], json!(U128(0)));
+
+    // only stray stable coin is recovered
+    let res = owner
+        .call(&worker, escrow_contract.id(), "recover_ft")
+        .args_json((stable_coin_contract.id(), U128(1001)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+    let res = owner
+        .call(&worker, escrow_contract.id(), "recover_ft")
+        .args_json((stable_coin_contract.id(), U128(1000)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success() && res.failures().is_empty());
+
+    assert_invariants(&worker, &escrow_contract).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_storage_management() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, finder, _, _) = init(&worker).await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let storage_balance = escrow_contract
+        .view(&worker, "storage_balance_of", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(storage_balance["total"], ESCROW_STORAGE_DEPOSIT.to_string());
+    assert_eq!(storage_balance["available"], (ESCROW_STORAGE_DEPOSIT - STORAGE_ACCOUNT_COST).to_string());
+
+    // withdraw all available deposit
+    let res = alice
+        .call(&worker, escrow_contract.id(), "storage_withdraw")
+        .args_json((Option::<U128>::None,))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // buy without storage deposit is refunded
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(&worker, "calculate_buy_proxy_token", json!({"amount": amount}).to_string().into_bytes())
+        .await?
+        .json::<u128>()?;
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert_eq!(escrow_contract.call(&worker, "get_circulating_supply")
+        .view()
+        .await?
+        .json::<u128>()?, 0);
+
+    // deposit storage of 3 proxy tokens
+    alice
+        .call(&worker, escrow_contract.id(), "storage_deposit")
+        .args_json((Option::<AccountId>::None, Option::<bool>::None))?
+        .deposit(3 * DEPOSIT_ONE_PT_MINT)
+        .max_gas()
+        .transact()
+        .await?;
+    let res = alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    assert_eq!(escrow_contract.call(&worker, "get_circulating_supply")
+        .view()
+        .await?
+        .json::<u128>()?, 3);
+
+    let storage_balance = escrow_contract
+        .view(&worker, "storage_balance_of", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(storage_balance["available"], "0");
+
+    // selling releases storage
+    let res = alice
+        .call(&worker, escrow_contract.id(), "sell")
+        .args_json(json!({"token_ids": vec!["0".to_string(), "1".to_string()]}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let storage_balance = escrow_contract
+        .view(&worker, "storage_balance_of", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(storage_balance["available"], (2 * DEPOSIT_ONE_PT_MINT).to_string());
+
+    // can not unregister holding proxy tokens
+    let res = alice
+        .call(&worker, escrow_contract.id(), "storage_unregister")
+        .args_json((Option::<bool>::None,))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // repeated buys and sells keep vote checkpoints compacted
+    for _ in 0..3 {
+        let coin_amount = escrow_contract
+            .view(&worker, "calculate_buy_proxy_token", json!({"amount": U128(1)}).to_string().into_bytes())
+            .await?
+            .json::<u128>()?;
+        alice
+            .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+            .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, String::from("buy:1")))?
+            .deposit(1u128)
+            .max_gas()
+            .transact()
+            .await?;
+        let token_ids = escrow_contract
+            .view(&worker, "pt_tokens_for_owner", json!({"account_id": alice.id()}).to_string().into_bytes())
+            .await?
+            .json::<Vec<String>>()?;
+        let res = alice
+            .call(&worker, escrow_contract.id(), "sell")
+            .args_json(json!({"token_ids": token_ids}))?
+            .max_gas()
+            .transact()
+            .await?;
+        assert!(res.is_success());
+    }
+    let storage_balance = escrow_contract
+        .view(&worker, "storage_balance_of", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(storage_balance["total"], (STORAGE_ACCOUNT_COST + 3 * DEPOSIT_ONE_PT_MINT).to_string());
+    assert_eq!(storage_balance["available"], (3 * DEPOSIT_ONE_PT_MINT).to_string());
+    assert_eq!(escrow_contract
+        .view(&worker, "get_vote_weight", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(0));
+
+    // records of account are kept, index deposit is refunded
+    let res = alice
+        .call(&worker, escrow_contract.id(), "storage_unregister")
+        .args_json((Option::<bool>::None,))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    assert!(escrow_contract
+        .view(&worker, "storage_balance_of", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<Option<serde_json::Value>>()?
+        .is_none());
+    assert_invariants(&worker, &escrow_contract).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pending_payout() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, _, bob, finder, _, _) = init(&worker).await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(&worker, "calculate_buy_proxy_token", json!({"amount": amount}).to_string().into_bytes())
+        .await?
+        .json::<u128>()?;
+    owner
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // pass buffer and conversion period
+    worker.fast_forward(1200).await?;
+
+    let project_info = escrow_contract.call(&worker, "get_project_info")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    let claimable_fund = project_info["claimable_fund"].as_str().unwrap().parse::<u128>()?;
+    let claim_amount = claimable_fund / 2;
+
+    // bob is not registered on stable coin, failed transfer is kept for bob
+    owner
+        .call(&worker, escrow_contract.id(), "claim_fund")
+        .args_json(json!({"to": bob.id(), "amount": U128(claim_amount)}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert_eq!(escrow_contract
+        .view(&worker, "get_pending_payout", json!({"account_id": bob.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(claim_amount));
+    assert_invariants(&worker, &escrow_contract).await?;
+
+    // bob registers and withdraws
+    let res = bob
+        .call(&worker, escrow_contract.id(), "withdraw_pending")
+        .args_json(json!({}))?
+        .deposit(FT_STORAGE_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    assert_eq!(stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": bob.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(claim_amount));
+    assert_eq!(escrow_contract
+        .view(&worker, "get_pending_payout", json!({"account_id": bob.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(0));
+
+    // nothing more to withdraw
+    let res = bob
+        .call(&worker, escrow_contract.id(), "withdraw_pending")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // attached deposit registers receiver before transfer, unused deposit is refunded to caller
+    let carol = worker.dev_create_account().await?;
+    let balance_before = worker.view_account(owner.id()).await?.balance;
+    let res = owner
+        .call(&worker, escrow_contract.id(), "claim_fund")
+        .args_json(json!({"to": carol.id(), "amount": U128(claimable_fund - claim_amount)}))?
+        .deposit(100 * FT_STORAGE_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    let balance_after = worker.view_account(owner.id()).await?.balance;
+    assert!(balance_before - balance_after < FT_STORAGE_DEPOSIT + 10_000 * STORAGE_BYTE_COST);
+    assert_eq!(stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": carol.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(claimable_fund - claim_amount));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pending_treasury_fee() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, bob, finder, _, _) = init(&worker).await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // bob is not registered on stable coin
+    owner
+        .call(&worker, escrow_contract.id(), "set_treasury_id")
+        .args_json(json!({"treasury_id": bob.id()}))?
+        .max_gas()
+        .transact()
+        .await?;
+    // transfer fee on every buy as escrows deployed before accrual
+    owner
+        .call(&worker, escrow_contract.id(), "set_treasury_fee_accrual")
+        .args_json(json!({"enabled": false}))?
+        .max_gas()
+        .transact()
+        .await?;
+
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(&worker, "calculate_buy_proxy_token", json!({"amount": amount}).to_string().into_bytes())
+        .await?
+        .json::<u128>()?;
+    let res = alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let treasury_fee = escrow_contract.call(&worker, "get_treasury_fee")
+        .view()
+        .await?
+        .json::<u32>()?;
+    let fee_amount = coin_amount * treasury_fee as u128 / 10_000;
+    let pending_payouts = escrow_contract
+        .view(&worker, "get_pending_payouts", json!({}).to_string().into_bytes())
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(pending_payouts, json!([{"account_id": bob.id(), "amount": U128(fee_amount)}]));
+    assert_invariants(&worker, &escrow_contract).await?;
+
+    let res = bob
+        .call(&worker, escrow_contract.id(), "withdraw_pending")
+        .args_json(json!({}))?
+        .deposit(FT_STORAGE_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    assert_eq!(stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": bob.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(fee_amount));
+    assert_eq!(escrow_contract
+        .view(&worker, "get_pending_payouts", json!({}).to_string().into_bytes())
+        .await?
+        .json::<serde_json::Value>()?, json!([]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_claim_treasury_fee() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, finder, treasury, _) = init(&worker).await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(&worker, "calculate_buy_proxy_token", json!({"amount": amount}).to_string().into_bytes())
+        .await?
+        .json::<u128>()?;
+    for _ in 0..2 {
+        let res = alice
+            .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+            .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+            .deposit(1u128)
+            .max_gas()
+            .transact()
+            .await?;
+        assert!(res.is_success());
+    }
+
+    // fee stays in escrow until treasury claims
+    let treasury_fee = escrow_contract.call(&worker, "get_treasury_fee")
+        .view()
+        .await?
+        .json::<u32>()?;
+    let fee_amount = coin_amount * treasury_fee as u128 / 10_000 * 2;
+    let accrued = escrow_contract.call(&worker, "get_accrued_treasury_fee")
+        .view()
+        .await?
+        .json::<U128>()?;
+    assert_eq!(accrued.0, fee_amount);
+    assert_eq!(stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": treasury.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(0));
+    assert_invariants(&worker, &escrow_contract).await?;
+
+    // only treasury claims
+    let res = alice
+        .call(&worker, escrow_contract.id(), "claim_treasury_fee")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let res = treasury
+        .call(&worker, escrow_contract.id(), "claim_treasury_fee")
+        .args_json(json!({"amount": U128(fee_amount + 1)}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let res = treasury
+        .call(&worker, escrow_contract.id(), "claim_treasury_fee")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    assert_eq!(stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": treasury.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(fee_amount));
+    let accrued = escrow_contract.call(&worker, "get_accrued_treasury_fee")
+        .view()
+        .await?
+        .json::<U128>()?;
+    assert_eq!(accrued.0, 0);
+    assert_invariants(&worker, &escrow_contract).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sell_fee() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, finder, _, _) = init(&worker).await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // sell fee is capped
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_sell_fee")
+        .args_json(json!({"fee": 2000, "destination": "Treasury"}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_sell_fee")
+        .args_json(json!({"fee": 500, "destination": "Treasury"}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(&worker, "calculate_buy_proxy_token", json!({"amount": amount}).to_string().into_bytes())
+        .await?
+        .json::<u128>()?;
+    let res = alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let total_fund_amount = escrow_contract.call(&worker, "get_total_fund_amount")
+        .view()
+        .await?
+        .json::<u128>()?;
+    let accrued_buy_fee = escrow_contract.call(&worker, "get_accrued_treasury_fee")
+        .view()
+        .await?
+        .json::<U128>()?;
+    let token_ids = vec!["0".to_string()];
+    let refund = escrow_contract
+        .view(&worker, "calculate_sell_proxy_token", json!({"token_ids": token_ids}).to_string().into_bytes())
+        .await?
+        .json::<u128>()?;
+
+    let res = alice
+        .call(&worker, escrow_contract.id(), "sell")
+        .args_json(json!({"token_ids": token_ids}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // sell fee leaves reserve as accrued treasury fee
+    let sell_fee = escrow_contract.call(&worker, "get_accrued_treasury_fee")
+        .view()
+        .await?
+        .json::<U128>()?.0 - accrued_buy_fee.0;
+    assert!(sell_fee > 0);
+    assert_eq!((refund + sell_fee) * 500 / 10_000, sell_fee);
+    assert_eq!(escrow_contract.call(&worker, "get_total_fund_amount")
+        .view()
+        .await?
+        .json::<u128>()?, total_fund_amount - refund - sell_fee);
+    assert_invariants(&worker, &escrow_contract).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_referral_rewards() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, bob, finder, _, _) = init(&worker).await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    // referral reward is taken from treasury fee
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_referral_fee")
+        .args_json(json!({"fee": 200}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_referral_fee")
+        .args_json(json!({"fee": 50}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(&worker, "calculate_buy_proxy_token", json!({"amount": amount}).to_string().into_bytes())
+        .await?
+        .json::<u128>()?;
+
+    // referrer should be registered for storage of its rewards
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}:{}", amount.0, bob.id())))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert_eq!(escrow_contract.call(&worker, "get_circulating_supply")
+        .view()
+        .await?
+        .json::<u128>()?, 0);
+    let res = bob
+        .call(&worker, escrow_contract.id(), "storage_deposit")
+        .args_json((Option::<AccountId>::None, Some(true)))?
+        .deposit(STORAGE_ACCOUNT_COST)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let res = alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}:{}", amount.0, bob.id())))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let treasury_fee = escrow_contract.call(&worker, "get_treasury_fee")
+        .view()
+        .await?
+        .json::<u32>()?;
+    let reward = coin_amount * 50 / 10_000;
+    let stats = escrow_contract
+        .view(&worker, "get_referral_stats", json!({"account_id": bob.id()}).to_string().into_bytes())
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(stats["referred_buys"], 1);
+    assert_eq!(stats["referred_amount"], coin_amount.to_string());
+    assert_eq!(stats["claimable_reward"], reward.to_string());
+    assert_eq!(escrow_contract.call(&worker, "get_accrued_treasury_fee")
+        .view()
+        .await?
+        .json::<U128>()?, U128(coin_amount * treasury_fee as u128 / 10_000 - reward));
+    assert_invariants(&worker, &escrow_contract).await?;
+
+    // bob is not registered on stable coin
+    let res = bob
+        .call(&worker, escrow_contract.id(), "claim_referral_rewards")
+        .args_json(json!({}))?
+        .deposit(FT_STORAGE_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    assert_eq!(stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": bob.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(reward));
+
+    let stats = escrow_contract
+        .view(&worker, "get_referral_stats", json!({"account_id": bob.id()}).to_string().into_bytes())
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(stats["claimed_reward"], reward.to_string());
+    assert_eq!(stats["claimable_reward"], "0");
+
+    let res = bob
+        .call(&worker, escrow_contract.id(), "claim_referral_rewards")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // buys without reward are not recorded
+    let res = owner
+        .call(&worker, escrow_contract.id(), "set_referral_fee")
+        .args_json(json!({"fee": 0}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    let coin_amount = escrow_contract
+        .view(&worker, "calculate_buy_proxy_token", json!({"amount": amount}).to_string().into_bytes())
+        .await?
+        .json::<u128>()?;
+    let res = alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}:{}", amount.0, bob.id())))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    let stats = escrow_contract
+        .view(&worker, "get_referral_stats", json!({"account_id": bob.id()}).to_string().into_bytes())
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(stats["referred_buys"], 1);
+    assert_invariants(&worker, &escrow_contract).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_nft_royalties() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, _, owner, _, _, finder, _, _) = init(&worker).await?;
+
+    // total royalty is capped
+    let res = owner
+        .call(&worker, escrow_contract.id(), "active_nft_project")
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Some(HashMap::from([(owner.id().to_string(), 1500u32), (finder.id().to_string(), 1000u32)]))))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    let res = owner
+        .call(&worker, escrow_contract.id(), "active_nft_project")
+        .args_json((NFT_BASE_URI, NFT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None, Some(HashMap::from([(owner.id().to_string(), 500u32)]))))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success() && res.failures().is_empty());
+
+    let project_token_id = escrow_contract.call(&worker, "get_project_token_id")
+        .view()
+        .await?
+        .json::<AccountId>()?;
+    let royalties = worker
+        .view(&project_token_id, "get_royalties", json!({}).to_string().into_bytes())
+        .await?
+        .json::<HashMap<String, u32>>()?;
+    assert_eq!(royalties, HashMap::from([(owner.id().to_string(), 500u32)]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_holders_after_v0_migrate() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let (escrow_contract, stable_coin_contract, owner, alice, _, finder, _, _) = init(&worker).await?;
+
+    owner
+        .call(&worker, escrow_contract.id(), "active_ft_project".into())
+        .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
+        .deposit(ACTIVATION_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+
+    //buy proxy token
+    let amount = U128::from(3u128);
+    let coin_amount = escrow_contract
+        .view(
+            &worker,
+            "calculate_buy_proxy_token",
+            json!({
+            "amount": amount
+        }).to_string().into_bytes(),
+        )
+        .await?
+        .json::<u128>()?;
+
+    alice
+        .call(&worker, stable_coin_contract.id(), "ft_transfer_call".into())
+        .args_json((escrow_contract.id(), U128(coin_amount), Option::<String>::None, format!("buy:{}", amount.0)))?
+        .deposit(1u128)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let token_ids = escrow_contract
+        .view(
+            &worker,
+            "pt_tokens_for_owner",
+            json!({"account_id": alice.id()}).to_string().into_bytes(),
+        )
+        .await?
+        .json::<Vec<String>>()?;
+    assert_eq!(token_ids.len(), 3);
+
+    let escrow_v0 = init_v0_escrow(&worker, &escrow_contract, &stable_coin_contract, &owner).await?;
+    assert_eq!(escrow_v0.call(&worker, "get_state_version")
+        .view()
+        .await?
+        .json::<u8>()?, 1);
+
+    // only owner upgrades migrated escrow
+    let res = alice
+        .call(&worker, escrow_v0.id(), "upgrade")
+        .args_json(json!({"code": Base64VecU8::from(NFT_ESCROW_CODE.to_vec())}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // holders are not indexed yet
+    let res = alice
+        .call(&worker, escrow_v0.id(), "sell")
+        .args_json(json!({"token_ids": vec![token_ids[0].clone()]}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+    assert!(escrow_v0
+        .view(&worker, "pt_tokens_for_owner", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<Vec<String>>()?
+        .is_empty());
+
+    // only owner indexes holders
+    let res = alice
+        .call(&worker, escrow_v0.id(), "migrate_holders")
+        .args_json(json!({"account_ids": [alice.id()], "from_index": 0, "limit": 10}))?
+        .deposit(10_000 * STORAGE_BYTE_COST)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // token range is paginated
+    let res = owner
+        .call(&worker, escrow_v0.id(), "migrate_holders")
+        .args_json(json!({"account_ids": [alice.id()], "from_index": 0, "limit": 2}))?
+        .deposit(10_000 * STORAGE_BYTE_COST)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    assert_eq!(escrow_v0
+        .view(&worker, "get_vote_weight", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(2));
+
+    // owner finishes migration although a token is not indexed
+    let res = alice
+        .call(&worker, escrow_v0.id(), "finish_holders_migration")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+    let res = owner
+        .call(&worker, escrow_v0.id(), "finish_holders_migration")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+    let res = owner
+        .call(&worker, escrow_v0.id(), "finish_holders_migration")
+        .args_json(json!({}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(!res.is_success());
+
+    // missed token is indexed later, indexed tokens are skipped
+    let res = owner
+        .call(&worker, escrow_v0.id(), "migrate_holders")
+        .args_json(json!({"account_ids": [alice.id()], "from_index": 0, "limit": 10}))?
+        .deposit(10_000 * STORAGE_BYTE_COST)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    let mut indexed_ids = escrow_v0
+        .view(&worker, "pt_tokens_for_owner", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<Vec<String>>()?;
+    indexed_ids.sort();
+    let mut held_ids = token_ids.clone();
+    held_ids.sort();
+    assert_eq!(indexed_ids, held_ids);
+    assert_eq!(escrow_v0
+        .view(&worker, "get_vote_weight", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(3));
+
+    let balance_before = stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?;
+
+    let res = alice
+        .call(&worker, escrow_v0.id(), "sell")
+        .args_json(json!({"token_ids": vec![token_ids[0].clone()]}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success() && res.failures().is_empty());
+
+    let balance_after = stable_coin_contract
+        .view(&worker, "ft_balance_of", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?;
+    assert!(balance_after.0 > balance_before.0);
+
+    // sell is settled and account is unlocked
+    let pending = escrow_v0.call(&worker, "get_pending_operations")
+        .view()
+        .await?
+        .json::<serde_json::Value>()?;
+    assert_eq!(pending["sell_refund"], "0");
+    assert_eq!(pending["sell_amount"], "0");
+    let is_pending = escrow_v0
+        .view(&worker, "is_account_pending", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<bool>()?;
+    assert!(!is_pending);
+    assert_eq!(escrow_v0.call(&worker, "get_circulating_supply")
+        .view()
+        .await?
+        .json::<u128>()?, 2);
+    assert_eq!(escrow_v0
+        .view(&worker, "get_vote_weight", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(2));
+
+    // vote with migrated weight
+    let proposal_id = alice
+        .call(&worker, escrow_v0.id(), "create_halt_proposal")
+        .args_json(json!({"reason": "project abandoned"}))?
+        .max_gas()
+        .transact()
+        .await?
+        .json::<u64>()?;
+    let res = alice
+        .call(&worker, escrow_v0.id(), "vote_halt")
+        .args_json(json!({"proposal_id": proposal_id, "support": false}))?
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success());
+
+    // pass buffer period, attached deposit registers alice on project token
+    worker.fast_forward(400).await?;
+    let res = alice
+        .call(&worker, escrow_v0.id(), "convert")
+        .args_json(json!({"token_ids": vec![token_ids[1].clone()]}))?
+        .deposit(FT_STORAGE_DEPOSIT)
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(res.is_success() && res.failures().is_empty());
+
+    let project_token_id = escrow_v0.call(&worker, "get_project_token_id")
+        .view()
+        .await?
+        .json::<AccountId>()?;
+    assert_eq!(alice
+        .call(&worker, &project_token_id, "ft_balance_of")
+        .args_json((alice.id(),))?
+        .view()
+        .await?
+        .json::<U128>()?, U128(1));
+    // converted amount keeps voting weight
+    assert_eq!(escrow_v0
+        .view(&worker, "get_vote_weight", json!({"account_id": alice.id()}).to_string().into_bytes())
+        .await?
+        .json::<U128>()?, U128(2));
+
+    assert_invariants(&worker, &escrow_v0).await?;
+
+    Ok(())
+}
+
 // #[tokio::test]
 // async fn test_convert_after_closing_ft_project() -> anyhow::Result<()> {
 //     let worker = workspaces::sandbox().await?;
@@ -1239,7 +4337,7 @@ async fn test_convert_after_closing_nft_project() -> anyhow::Result<()> {
 //     // active project
 //     let res = owner
 //         .call(&worker, escrow_contract.id(), "active_ft_project".into())
-//         .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES))?
+//         .args_json((FT_MAX_SUPPLY, finder.id(), FUND_THRESHOLD, FIVE_MINUTES, TEN_MINUTES, Option::<String>::None))?
 //         .max_gas()
 //         .transact()
 //         .await?;
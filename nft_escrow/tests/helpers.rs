@@ -33,3 +33,66 @@ pub struct CurveArgs {
     pub arg_c: Option<u128>,
     pub arg_d: Option<u128>,
 }
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Phase {
+    NotActivated,
+    Funding,
+    Buffer,
+    Conversion,
+    Closed,
+    Refunding,
+}
+
+/// Escrow state layout before upgrade support, written with `patch_state` to test `migrate`.
+/// Account ids are borsh encoded as strings, collections as their storage prefixes.
+#[derive(BorshSerialize)]
+pub struct EscrowStateV0 {
+    pub owner_id: String,
+    pub name: String,
+    pub symbol: String,
+    pub treasury_id: String,
+    pub treasury_fee: u32,
+    pub finder_id: Option<String>,
+    pub finder_fee: u32,
+    /// 0: NonFungible, 1: Fungible
+    pub project_token_type: u8,
+    pub project_token_id: Option<String>,
+    pub fund_threshold: u128,
+    pub start_timestamp: u64,
+    pub tp_timestamp: u64,
+    pub buffer_period: u64,
+    pub conversion_period: u64,
+    pub stable_coin_id: String,
+    pub stable_coin_decimals: u8,
+    pub total_fund_amount: u128,
+    pub claimed_fund_amount: u128,
+    pub claimed_finder_fee: u128,
+    pub pre_mint_amount: u128,
+    pub converted_amount: u128,
+    pub circulating_supply: u128,
+    pub curve_type: CurveType,
+    pub curve_args: CurveArgs,
+    /// 0: Running, 1: Paused
+    pub state: u8,
+    /// 0: None, 1: PreMint, 2: RemainProxy, 3: TransferOwnership
+    pub closed_step: u8,
+    pub pt_media_uri: String,
+    pub pt_max_supply: u128,
+    pub pt_all_total_supply: u128,
+    /// `LookupMap` prefix
+    pub pt_total_supply: Vec<u8>,
+    /// `UnorderedMap` key index prefix, keys `Vector` and values `Vector`
+    pub pt_balances_per_token: (Vec<u8>, (u64, Vec<u8>), (u64, Vec<u8>)),
+}
+
+/// Prefix of proxy token total supplies, `StorageKey::TotalSupply { supply: u128::MAX }`
+pub fn pt_total_supply_prefix() -> Vec<u8> {
+    [vec![0u8], u128::MAX.to_le_bytes().to_vec()].concat()
+}
+
+/// Prefix of proxy token balances, `StorageKey::Balances`
+pub fn pt_balances_prefix() -> Vec<u8> {
+    vec![1u8]
+}